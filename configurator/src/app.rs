@@ -2,6 +2,11 @@ use cosmic::{
     Element,
     app::{Core, Task},
     executor,
+    iced::{
+        Event, Subscription, event,
+        keyboard::{self, Key, Modifiers},
+        window,
+    },
     iced_widget::text_input,
     widget::{
         self, button,
@@ -16,7 +21,9 @@ use crate::{
     message::{AppMsg, ChangeMsg, PageMsg},
     node::data_path::DataPathType,
     page::{self, Page, create_pages},
+    providers::Registry,
     view::view_app,
+    watcher,
 };
 
 pub const QUALIFIER: &str = "io.github";
@@ -52,6 +59,31 @@ impl App {
     }
 }
 
+/// Key presses which are not handled by a widget. A focused text input
+/// captures the shortcuts it handles itself, like Ctrl+Z.
+fn on_event(event: Event, status: event::Status, _window: window::Id) -> Option<AppMsg> {
+    match (event, status) {
+        (
+            Event::Keyboard(keyboard::Event::KeyPressed { key, modifiers, .. }),
+            event::Status::Ignored,
+        ) => on_key_press(key, modifiers),
+        _ => None,
+    }
+}
+
+fn on_key_press(key: Key, modifiers: Modifiers) -> Option<AppMsg> {
+    match key {
+        Key::Character(c) if modifiers.control() && c.eq_ignore_ascii_case("z") => {
+            if modifiers.shift() {
+                Some(AppMsg::Redo)
+            } else {
+                Some(AppMsg::Undo)
+            }
+        }
+        _ => None,
+    }
+}
+
 impl cosmic::Application for App {
     type Executor = executor::Default;
     type Message = AppMsg;
@@ -77,7 +109,9 @@ impl cosmic::Application for App {
 
         let mut active = false;
 
-        for page in create_pages(config.settings()) {
+        let registry = Registry::default();
+
+        for page in create_pages(config.settings(), &registry) {
             if let Some(appid) = &config.settings().last_used_page
                 && appid == &page.appid
             {
@@ -148,6 +182,28 @@ impl cosmic::Application for App {
                     error!("{err}");
                 }
             }
+            AppMsg::ConfigChanged(path) => {
+                let entities = self.nav_model.iter().collect::<Vec<_>>();
+
+                for entity in entities {
+                    if let Some(page) = self.nav_model.data_mut::<Page>(entity)
+                        && page
+                            .watched_paths()
+                            .iter()
+                            .any(|watched_path| watched_path.contains(&path))
+                    {
+                        page.on_config_changed();
+                    }
+                }
+            }
+            AppMsg::Undo => {
+                let id = self.nav_model.active();
+                return self.update(AppMsg::PageMsg(id, PageMsg::Undo));
+            }
+            AppMsg::Redo => {
+                let id = self.nav_model.active();
+                return self.update(AppMsg::PageMsg(id, PageMsg::Redo));
+            }
             AppMsg::ReloadLocalConfig => {
                 self.config.reload().unwrap();
             }
@@ -220,6 +276,20 @@ impl cosmic::Application for App {
         })
     }
 
+    fn subscription(&self) -> Subscription<Self::Message> {
+        let watched_paths = self
+            .nav_model
+            .iter()
+            .filter_map(|entity| self.nav_model.data::<Page>(entity))
+            .flat_map(Page::watched_paths)
+            .collect();
+
+        Subscription::batch([
+            event::listen_with(on_event),
+            watcher::subscription(watched_paths).map(AppMsg::ConfigChanged),
+        ])
+    }
+
     fn header_end(&self) -> Vec<Element<'_, Self::Message>> {
         vec![
             button::text("reload")
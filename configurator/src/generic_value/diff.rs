@@ -0,0 +1,283 @@
+use super::*;
+
+/// A key changed to different values by two versions of a config,
+/// which both come from `base`. [`Value::Empty`] means the key is absent.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyDiff {
+    /// Keys from the root of the config
+    pub path: Vec<String>,
+    pub base: Value,
+    pub mine: Value,
+    pub theirs: Value,
+}
+
+/// Fields of structs and maps, which are compared key by key.
+/// An absent value is an object without fields.
+fn fields(value: &Value) -> Option<Option<&Map<String, Value>>> {
+    match value {
+        Value::Empty => Some(None),
+        Value::Map(map) | Value::Struct(_, map) => Some(Some(map)),
+        _ => None,
+    }
+}
+
+fn get<'a>(fields: Option<&'a Map<String, Value>>, key: &str) -> &'a Value {
+    fields
+        .and_then(|map| map.0.get(key))
+        .unwrap_or(&Value::Empty)
+}
+
+/// Keys of the three versions, in the order of `mine`, then `theirs`, then `base`
+fn keys<'a>(
+    base: Option<&'a Map<String, Value>>,
+    mine: Option<&'a Map<String, Value>>,
+    theirs: Option<&'a Map<String, Value>>,
+) -> Vec<&'a String> {
+    let mut keys: Vec<&String> = Vec::new();
+
+    for map in [mine, theirs, base].into_iter().flatten() {
+        for key in map.0.keys() {
+            if !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+    }
+
+    keys
+}
+
+impl Value {
+    /// Equality which ignores the kind of numbers: a value of the tree
+    /// has the kind of its schema, while an unsuffixed number read from
+    /// a file has the default kind of its format.
+    pub fn equivalent(&self, other: &Value) -> bool {
+        fn all_equivalent(a: &[Value], b: &[Value]) -> bool {
+            a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.equivalent(b))
+        }
+
+        fn fields_equivalent(a: &Map<String, Value>, b: &Map<String, Value>) -> bool {
+            a.len() == b.len()
+                && a.iter()
+                    .all(|(key, a)| b.0.get(key).is_some_and(|b| a.equivalent(b)))
+        }
+
+        match (self, other) {
+            (Value::Number(a), Value::Number(b)) => a.equivalent(b),
+            (Value::Option(Some(a)), Value::Option(Some(b))) => a.equivalent(b),
+            (Value::Array(a), Value::Array(b)) | (Value::Tuple(a), Value::Tuple(b)) => {
+                all_equivalent(a, b)
+            }
+            (Value::TupleStruct(name_a, a), Value::TupleStruct(name_b, b)) => {
+                name_a == name_b && all_equivalent(a, b)
+            }
+            (Value::Map(a), Value::Map(b)) => fields_equivalent(a, b),
+            (Value::Struct(name_a, a), Value::Struct(name_b, b)) => {
+                name_a == name_b && fields_equivalent(a, b)
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// Three-way diff of two versions of `base`. Structs and maps are
+    /// compared key by key, other values as a whole. Only the keys changed
+    /// by both versions, to different values, are returned: the others
+    /// can be merged with [`Value::merge3`].
+    pub fn diff3(base: &Value, mine: &Value, theirs: &Value) -> Vec<KeyDiff> {
+        fn diff3_rec(
+            path: &mut Vec<String>,
+            base: &Value,
+            mine: &Value,
+            theirs: &Value,
+            diffs: &mut Vec<KeyDiff>,
+        ) {
+            if mine.equivalent(theirs) || mine.equivalent(base) || theirs.equivalent(base) {
+                return;
+            }
+
+            match (fields(base), fields(mine), fields(theirs)) {
+                (Some(base_fields), Some(mine_fields), Some(theirs_fields)) => {
+                    for key in keys(base_fields, mine_fields, theirs_fields) {
+                        path.push(key.clone());
+                        diff3_rec(
+                            path,
+                            get(base_fields, key),
+                            get(mine_fields, key),
+                            get(theirs_fields, key),
+                            diffs,
+                        );
+                        path.pop();
+                    }
+                }
+                _ => diffs.push(KeyDiff {
+                    path: path.clone(),
+                    base: base.clone(),
+                    mine: mine.clone(),
+                    theirs: theirs.clone(),
+                }),
+            }
+        }
+
+        let mut diffs = Vec::new();
+        diff3_rec(&mut Vec::new(), base, mine, theirs, &mut diffs);
+        diffs
+    }
+
+    /// Three-way merge of two versions of `base`: each key takes the
+    /// version which changed it. When both changed it, `mine` wins.
+    pub fn merge3(base: &Value, mine: &Value, theirs: &Value) -> Value {
+        if mine.equivalent(theirs) || theirs.equivalent(base) {
+            return mine.clone();
+        }
+
+        if mine.equivalent(base) {
+            return theirs.clone();
+        }
+
+        match (fields(base), fields(mine), fields(theirs)) {
+            (Some(base_fields), Some(mine_fields), Some(theirs_fields)) => {
+                let mut map = Map::new();
+
+                for key in keys(base_fields, mine_fields, theirs_fields) {
+                    let value = Value::merge3(
+                        get(base_fields, key),
+                        get(mine_fields, key),
+                        get(theirs_fields, key),
+                    );
+
+                    if value.is_not_empty() {
+                        map.0.insert(key.clone(), value);
+                    }
+                }
+
+                // keep the kind of object of the version we have
+                match mine.if_not_empty(theirs) {
+                    Value::Struct(name, _) => Value::Struct(name.clone(), map),
+                    _ => Value::Map(map),
+                }
+            }
+            _ => mine.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn map(entries: &[(&str, Value)]) -> Value {
+        Value::Map(
+            entries
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.clone()))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn diff3_keys() {
+        let base = map(&[("a", Value::from(1)), ("b", Value::from(1))]);
+        let mine = map(&[
+            ("a", Value::from(2)),
+            ("b", Value::from(2)),
+            ("c", map(&[("d", Value::from(2))])),
+        ]);
+        let theirs = map(&[
+            ("a", Value::from(2)),
+            ("b", Value::from(3)),
+            ("c", map(&[("d", Value::from(3)), ("e", Value::from(3))])),
+        ]);
+
+        // a is changed to the same value, c/e only by theirs
+        assert_eq!(
+            Value::diff3(&base, &mine, &theirs),
+            vec![
+                KeyDiff {
+                    path: vec!["b".into()],
+                    base: Value::from(1),
+                    mine: Value::from(2),
+                    theirs: Value::from(3),
+                },
+                KeyDiff {
+                    path: vec!["c".into(), "d".into()],
+                    base: Value::Empty,
+                    mine: Value::from(2),
+                    theirs: Value::from(3),
+                },
+            ]
+        );
+
+        assert!(Value::diff3(&base, &mine, &mine).is_empty());
+    }
+
+    #[test]
+    fn changed_by_theirs_only() {
+        let base = map(&[("a", Value::from(1)), ("b", Value::from(1))]);
+        let mine = map(&[("a", Value::from(2)), ("b", Value::from(1))]);
+        let theirs = map(&[
+            ("a", Value::from(1)),
+            ("b", Value::from(3)),
+            ("c", Value::from(3)),
+        ]);
+
+        // no conflict: the changes are merged
+        assert!(Value::diff3(&base, &mine, &theirs).is_empty());
+        assert_eq!(
+            Value::merge3(&base, &mine, &theirs),
+            map(&[
+                ("a", Value::from(2)),
+                ("b", Value::from(3)),
+                ("c", Value::from(3)),
+            ])
+        );
+    }
+
+    #[test]
+    fn number_kinds() {
+        // the tree has the kinds of the schema, the files unsuffixed numbers
+        let base = map(&[
+            ("a", Value::Number(Number::U128(1))),
+            ("b", Value::Number(Number::U128(1))),
+        ]);
+        let mine = map(&[
+            ("a", Value::Number(Number::U32(1))),
+            ("b", Value::Number(Number::U32(1))),
+        ]);
+        let theirs = map(&[
+            ("a", Value::Number(Number::U128(1))),
+            ("b", Value::Number(Number::U128(2))),
+        ]);
+
+        // mine didn't change anything
+        assert!(Value::diff3(&base, &mine, &theirs).is_empty());
+        assert_eq!(Value::merge3(&base, &mine, &theirs), theirs);
+    }
+
+    #[test]
+    fn merge3_keys() {
+        let base = map(&[
+            ("a", Value::from(1)),
+            ("b", Value::from(1)),
+            ("c", Value::from(1)),
+        ]);
+        let mine = map(&[
+            ("a", Value::from(2)),
+            ("b", Value::from(1)),
+            ("c", Value::from(2)),
+        ]);
+        let theirs = map(&[
+            ("a", Value::from(1)),
+            ("b", Value::from(3)),
+            ("c", Value::from(3)),
+        ]);
+
+        // a is changed by mine only, b by theirs only, c by both
+        assert_eq!(
+            Value::merge3(&base, &mine, &theirs),
+            map(&[
+                ("a", Value::from(2)),
+                ("b", Value::from(3)),
+                ("c", Value::from(2)),
+            ])
+        );
+    }
+}
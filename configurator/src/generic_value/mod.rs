@@ -1,6 +1,7 @@
 mod map;
 mod number;
 
+mod diff;
 mod merge;
 
 use std::ops::Deref;
@@ -9,6 +10,8 @@ pub use number::{F32, F64, Number};
 
 pub use map::Map;
 
+pub use diff::KeyDiff;
+
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub enum Value {
     /// Represent the absence of value.
@@ -49,6 +52,14 @@ impl Value {
         }
     }
 
+    pub fn as_char(&self) -> Option<&char> {
+        if let Value::Char(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
     pub fn as_str(&self) -> Option<&str> {
         if let Value::String(v) = self {
             Some(v)
@@ -96,6 +107,14 @@ impl Value {
         }
     }
 
+    pub fn as_map(&self) -> Option<&Map<String, Value>> {
+        if let Value::Map(v) = self {
+            Some(v)
+        } else {
+            None
+        }
+    }
+
     pub fn as_tuple(&self) -> Option<&Vec<Value>> {
         if let Value::Tuple(v) = self {
             Some(v)
@@ -124,6 +143,28 @@ impl Value {
         }
     }
 
+    /// Read this value as an option, considering that a value which is not
+    /// an option is implicitly `Some`. Return [`None`] for [`Value::Empty`].
+    ///
+    /// This is needed because schema defaults don't wrap `Some` values.
+    pub fn as_implicit_option(&self) -> Option<Option<&Value>> {
+        match self {
+            Value::Empty => None,
+            Value::Option(value) => Some(value.as_deref()),
+            value => Some(Some(value)),
+        }
+    }
+
+    /// Name of the variant, if this value can represent an enum variant.
+    pub fn enum_variant_name(&self) -> Option<&str> {
+        match self {
+            Value::UnitStruct(name) => Some(name),
+            Value::TupleStruct(name, _) => Some(name),
+            Value::Struct(Some(name), _) => Some(name),
+            _ => None,
+        }
+    }
+
     pub fn as_unit_struct(&self) -> Option<&str> {
         if let Value::UnitStruct(name) = self {
             Some(name)
@@ -44,6 +44,14 @@ impl Display for Number {
 }
 
 impl Number {
+    /// Compare the values, whatever their kinds
+    pub fn equivalent(&self, other: &Number) -> bool {
+        match (self.as_i128(), other.as_i128()) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.to_string() == other.to_string(),
+        }
+    }
+
     pub fn as_u128(&self) -> Option<u128> {
         match *self {
             Number::U8(v) => Some(v.into()),
@@ -0,0 +1,250 @@
+use std::collections::VecDeque;
+
+use crate::node::{Node, NodeContainer, data_path::DataPathType};
+
+/// Maximum number of changes that can be undone
+const MAX_LEN: usize = 100;
+
+/// The inverse of a change: restoring it puts the tree back
+/// in the state it had before the change.
+#[derive(Debug)]
+struct HistoryEntry {
+    /// Path of the subtree which was changed
+    data_path: Vec<DataPathType>,
+    /// Previous subtree at `data_path`
+    node: NodeContainer,
+    /// Modified flags of the ancestors, from the root to the parent
+    modified: Vec<bool>,
+}
+
+impl HistoryEntry {
+    fn capture(tree: &NodeContainer, data_path: &[DataPathType]) -> Option<Self> {
+        // a change marks as modified all the nodes under an array, a map,
+        // an enum or an option (see `set_modified2`), so the subtree
+        // starts at the topmost of them
+        let len = (0..data_path.len())
+            .find(|pos| {
+                tree.get_at(Box::new(data_path[..*pos].iter()))
+                    .is_some_and(|node| {
+                        matches!(
+                            node.node,
+                            Node::Array(_) | Node::Map(_) | Node::Enum(_) | Node::Option(_)
+                        )
+                    })
+            })
+            .unwrap_or(data_path.len());
+        let data_path = &data_path[..len];
+
+        let node = tree.get_at(Box::new(data_path.iter()))?.clone();
+
+        let modified = (0..data_path.len())
+            .map(|pos| {
+                tree.get_at(Box::new(data_path[..pos].iter()))
+                    .map(|node| node.modified)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(Self {
+            data_path: data_path.to_vec(),
+            node,
+            modified,
+        })
+    }
+
+    /// Put back the subtree and the flags of this entry.
+    /// Return the entry that reverts this operation.
+    fn restore(self, tree: &mut NodeContainer) -> Option<Self> {
+        let inverse = Self::capture(tree, &self.data_path)?;
+
+        for (pos, modified) in self.modified.into_iter().enumerate() {
+            tree.get_at_mut(Box::new(self.data_path[..pos].iter()))?
+                .modified = modified;
+        }
+
+        *tree.get_at_mut(Box::new(self.data_path.iter()))? = self.node;
+
+        Some(inverse)
+    }
+}
+
+/// Undo and redo stacks of a page
+#[derive(Debug, Default)]
+pub struct History {
+    undo: VecDeque<HistoryEntry>,
+    redo: Vec<HistoryEntry>,
+}
+
+/// A snapshot taken before a change, which can be pushed
+/// in the history once the change is applied
+#[derive(Debug)]
+pub struct Snapshot(Option<HistoryEntry>);
+
+impl History {
+    /// Take a snapshot of the node at `data_path`, before changing it
+    pub fn snapshot(tree: &NodeContainer, data_path: &[DataPathType]) -> Snapshot {
+        Snapshot(HistoryEntry::capture(tree, data_path))
+    }
+
+    /// Record a change. This clears the redo stack.
+    pub fn push(&mut self, snapshot: Snapshot) {
+        let Some(entry) = snapshot.0 else {
+            error!("can't record a change of a node that doesn't exist");
+            return;
+        };
+
+        self.redo.clear();
+        self.push_undo(entry);
+    }
+
+    fn push_undo(&mut self, entry: HistoryEntry) {
+        if self.undo.len() == MAX_LEN {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(entry);
+    }
+
+    /// Revert the last change. Return false if there was nothing to undo.
+    pub fn undo(&mut self, tree: &mut NodeContainer) -> bool {
+        let Some(entry) = self.undo.pop_back() else {
+            return false;
+        };
+
+        match entry.restore(tree) {
+            Some(inverse) => {
+                self.redo.push(inverse);
+                true
+            }
+            None => {
+                error!("can't undo: the node doesn't exist anymore");
+                self.clear();
+                false
+            }
+        }
+    }
+
+    /// Apply again the last undone change. Return false if there was nothing to redo.
+    pub fn redo(&mut self, tree: &mut NodeContainer) -> bool {
+        let Some(entry) = self.redo.pop() else {
+            return false;
+        };
+
+        match entry.restore(tree) {
+            Some(inverse) => {
+                self.push_undo(inverse);
+                true
+            }
+            None => {
+                error!("can't redo: the node doesn't exist anymore");
+                self.clear();
+                false
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.undo.clear();
+        self.redo.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_schema2::{RustSchema, schema_for};
+
+    use super::*;
+    use crate::{generic_value::Value, providers::cosmic_ron::ron_value_to_value};
+
+    #[derive(RustSchema)]
+    #[allow(dead_code)]
+    struct Config {
+        a: u8,
+    }
+
+    fn tree() -> NodeContainer {
+        let root = schema_for::<Config>();
+        let schema = root.resolve_schema(&root.schema).unwrap();
+        let value = ron_value_to_value(ron_value::from_str("(a: 0)").unwrap()).unwrap();
+        NodeContainer::from_schema_and_value(&root, schema, &value, &Value::Empty)
+    }
+
+    fn path() -> Vec<DataPathType> {
+        vec![DataPathType::Name("a".to_string())]
+    }
+
+    fn value(tree: &NodeContainer) -> Option<String> {
+        let Node::Number(node_number) = &tree.get_at(Box::new(path().iter())).unwrap().node else {
+            panic!("not a number");
+        };
+        node_number.value.as_ref().map(|number| number.to_string())
+    }
+
+    /// Set `a` to `value` and record the change
+    fn change(history: &mut History, tree: &mut NodeContainer, value: u8) {
+        let snapshot = History::snapshot(tree, &path());
+
+        tree.get_at_mut(Box::new(path().iter()))
+            .unwrap()
+            .node
+            .unwrap_number_mut()
+            .set_from_str(value.to_string())
+            .unwrap();
+        tree.set_modified(&path());
+
+        history.push(snapshot);
+    }
+
+    #[test]
+    fn undo_redo() {
+        let mut history = History::default();
+        let mut tree = tree();
+
+        change(&mut history, &mut tree, 1);
+        change(&mut history, &mut tree, 2);
+
+        assert!(history.undo(&mut tree));
+        assert_eq!(value(&tree).as_deref(), Some("1"));
+        assert!(tree.modified);
+
+        assert!(history.undo(&mut tree));
+        assert_eq!(value(&tree).as_deref(), Some("0"));
+        assert!(!tree.modified);
+        assert!(!history.undo(&mut tree));
+
+        assert!(history.redo(&mut tree));
+        assert!(history.redo(&mut tree));
+        assert_eq!(value(&tree).as_deref(), Some("2"));
+        assert!(tree.modified);
+        assert!(!history.redo(&mut tree));
+    }
+
+    #[test]
+    fn max_len() {
+        let mut history = History::default();
+        let mut tree = tree();
+
+        for i in 0..MAX_LEN + 10 {
+            change(&mut history, &mut tree, (i % 200) as u8 + 1);
+        }
+
+        for _ in 0..MAX_LEN {
+            assert!(history.undo(&mut tree));
+        }
+        assert!(!history.undo(&mut tree));
+
+        // the 10 oldest changes were dropped
+        assert_eq!(value(&tree).as_deref(), Some("10"));
+    }
+
+    #[test]
+    fn change_clears_redo() {
+        let mut history = History::default();
+        let mut tree = tree();
+
+        change(&mut history, &mut tree, 1);
+        assert!(history.undo(&mut tree));
+
+        change(&mut history, &mut tree, 3);
+        assert!(!history.redo(&mut tree));
+        assert_eq!(value(&tree).as_deref(), Some("3"));
+    }
+}
@@ -8,6 +8,7 @@ extern crate tracing;
 mod app;
 mod config;
 mod generic_value;
+mod history;
 mod localize;
 mod message;
 mod node;
@@ -15,6 +16,7 @@ mod page;
 mod providers;
 mod utils;
 mod view;
+mod watcher;
 
 #[macro_use]
 mod icon;
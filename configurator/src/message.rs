@@ -1,12 +1,18 @@
+use std::path::PathBuf;
+
 use cosmic::widget::segmented_button::Entity;
 
-use crate::node::data_path::DataPathType;
+use crate::{node::data_path::DataPathType, page::Resolution};
 
 #[derive(Clone, Debug)]
 pub enum AppMsg {
     PageMsg(Entity, PageMsg),
     ReloadActivePage,
+    /// A config file changed on disk
+    ConfigChanged(PathBuf),
     ReloadLocalConfig,
+    Undo,
+    Redo,
     CloseDialog,
     DialogInput(String),
 }
@@ -19,6 +25,12 @@ pub enum PageMsg {
     ApplyDefault(Vec<DataPathType>),
     DialogAddNewNodeToObject(Vec<DataPathType>),
     DialogRenameKey(Vec<DataPathType>, String),
+    Undo,
+    Redo,
+    IgnoreReadError(usize),
+    /// Move the unreadable file to a `.bak` dotfile, so the default is used
+    ResetReadError(usize),
+    ResolveConflict(Resolution),
     None,
 }
 
@@ -27,9 +39,16 @@ pub enum ChangeMsg {
     ChangeBool(bool),
     ChangeString(String),
     ChangeNumber(String),
+    ChangeChar(String),
     ChangeEnum(usize),
+    ChangeOption(bool),
     Remove(DataPathType),
+    /// Remove a key of a struct that the schema doesn't describe
+    RemoveUnknown(String),
     AddNewNodeToObject(String),
     AddNewNodeToArray,
-    RenameKey { prev: String, new: String },
+    RenameKey {
+        prev: String,
+        new: String,
+    },
 }
@@ -185,6 +185,28 @@ impl DataPath {
                     {
                         node = field;
                     }
+                    (Node::Map(node_map), DataPathType::Name(key))
+                        if let Some(value) = &node_map.value
+                            && let Some(n) = value.get(key) =>
+                    {
+                        node = n;
+                    }
+                    (Node::Tuple(node_tuple), DataPathType::Indice(i))
+                        if let Some(field) = node_tuple.fields.get(*i) =>
+                    {
+                        node = field;
+                    }
+                    (Node::Enum(node_enum), DataPathType::Indice(i))
+                        if node_enum.value == Some(*i)
+                            && let Some(n) = node_enum.active_node() =>
+                    {
+                        node = n;
+                    }
+                    (Node::Option(node_option), DataPathType::Indice(0))
+                        if let Some(n) = node_option.some_node() =>
+                    {
+                        node = n;
+                    }
                     _ => return Some(pos),
                 }
             }
@@ -225,6 +247,28 @@ impl NodeContainer {
                 {
                     node = field;
                 }
+                (Node::Map(node_map), DataPathType::Name(key))
+                    if let Some(value) = &node_map.value
+                        && let Some(n) = value.get(key) =>
+                {
+                    node = n;
+                }
+                (Node::Tuple(node_tuple), DataPathType::Indice(pos))
+                    if let Some(field) = node_tuple.fields.get(*pos) =>
+                {
+                    node = field;
+                }
+                (Node::Enum(node_enum), DataPathType::Indice(pos))
+                    if node_enum.value == Some(*pos)
+                        && let Some(n) = node_enum.active_node() =>
+                {
+                    node = n;
+                }
+                (Node::Option(node_option), DataPathType::Indice(0))
+                    if let Some(n) = node_option.some_node() =>
+                {
+                    node = n;
+                }
                 _ => return None,
             }
         }
@@ -256,6 +300,20 @@ impl NodeContainer {
                         return None;
                     }
                 }
+                (Node::Map(node_map), DataPathType::Name(key)) => {
+                    node = node_map.value.as_mut()?.get_mut(key)?;
+                }
+                (Node::Tuple(node_tuple), DataPathType::Indice(pos)) => {
+                    node = node_tuple.fields.get_mut(*pos)?;
+                }
+                (Node::Enum(node_enum), DataPathType::Indice(pos))
+                    if node_enum.value == Some(*pos) =>
+                {
+                    node = node_enum.active_node_mut()?;
+                }
+                (Node::Option(node_option), DataPathType::Indice(0)) => {
+                    node = node_option.some_node_mut()?;
+                }
                 _ => return None,
             }
         }
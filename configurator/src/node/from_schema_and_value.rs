@@ -1,11 +1,23 @@
+use std::collections::HashSet;
+
 use indexmap::IndexMap;
-use rust_schema2::{RustSchema, RustSchemaKind, RustSchemaRoot};
+use rust_schema2::{
+    EnumVariant, EnumVariantKind, RustSchema, RustSchemaKind, RustSchemaOrRef, RustSchemaRoot,
+    StructField,
+};
 
 use crate::{
     generic_value::Value,
-    node::{Node, NodeArray, NodeContainer, NodeString, NodeStruct, rust_schema_value_to_value},
+    node::{
+        Node, NodeArray, NodeBool, NodeChar, NodeContainer, NodeEnum, NodeEnumVariant, NodeLazy,
+        NodeMap, NodeNumber, NodeOption, NodeString, NodeStruct, NodeTuple, NodeUnit,
+        rust_schema_value_to_value,
+    },
 };
 
+/// Nodes deeper than this are not built, see [`NodeLazy`].
+const MAX_DEPTH: usize = 3;
+
 impl NodeContainer {
     #[instrument(skip_all)]
     pub fn from_schema_and_value(
@@ -14,16 +26,66 @@ impl NodeContainer {
         value: &Value,
         default: &Value,
     ) -> Self {
+        Self::from_schema_and_value_at_depth(root, schema, value, default, 0)
+    }
+
+    fn from_schema_and_value_at_depth(
+        root: &RustSchemaRoot,
+        schema: &RustSchema,
+        value: &Value,
+        default: &Value,
+        depth: usize,
+    ) -> Self {
+        if depth > MAX_DEPTH && has_children(&schema.kind) {
+            return NodeContainer::from_node(Node::Lazy(NodeLazy {
+                value: value.clone(),
+                modified_value: Value::Empty,
+            }))
+            .set_default(default.clone());
+        }
+
         match &schema.kind {
-            RustSchemaKind::Unit => todo!(),
-            RustSchemaKind::Boolean => todo!(),
-            RustSchemaKind::Number(number_kind) => todo!(),
-            RustSchemaKind::Char => todo!(),
+            RustSchemaKind::Unit => NodeContainer::from_node(Node::Unit(NodeUnit { name: None }))
+                .set_default(default.clone()),
+            RustSchemaKind::Boolean => NodeContainer::from_node(Node::Bool(NodeBool {
+                value: value.as_bool().copied(),
+            }))
+            .set_default(default.clone()),
+            RustSchemaKind::Number(number_kind) => NodeContainer::from_node(Node::Number(
+                NodeNumber::new(number_kind.clone(), value.as_number()),
+            ))
+            .set_default(default.clone()),
+            RustSchemaKind::Char => {
+                NodeContainer::from_node(Node::Char(NodeChar::new(value.as_char().copied())))
+                    .set_default(default.clone())
+            }
             RustSchemaKind::String => NodeContainer::from_node(Node::String(NodeString {
                 value: value.as_str().map(|v| v.to_owned()),
             }))
             .set_default(default.clone()),
-            RustSchemaKind::Option(rust_schema_or_ref) => todo!(),
+            RustSchemaKind::Option(rust_schema_or_ref) => {
+                let schema = root.resolve_schema(rust_schema_or_ref).unwrap();
+
+                let default_some = default.as_implicit_option().flatten();
+
+                let (is_some, node) = match value.as_implicit_option() {
+                    Some(Some(value)) => (
+                        Some(true),
+                        Some(Box::new(Self::from_schema_and_value_at_depth(
+                            root,
+                            schema,
+                            value,
+                            default_some.unwrap_or(&Value::Empty),
+                            depth + 1,
+                        ))),
+                    ),
+                    Some(None) => (Some(false), None),
+                    None => (None, None),
+                };
+
+                NodeContainer::from_node(Node::Option(NodeOption { is_some, node }))
+                    .set_default(default.clone())
+            }
             RustSchemaKind::Array(array) => {
                 let value = if let Some(vec) = value.as_array() {
                     if let Some(template) = &array.template {
@@ -32,8 +94,14 @@ impl NodeContainer {
                         Some(
                             vec.iter()
                                 .map(|v| {
-                                    Self::from_schema_and_value(root, template, v, &Value::Empty)
-                                        .set_is_removable(true)
+                                    Self::from_schema_and_value_at_depth(
+                                        root,
+                                        template,
+                                        v,
+                                        &Value::Empty,
+                                        depth + 1,
+                                    )
+                                    .set_is_removable(true)
                                 })
                                 .collect(),
                         )
@@ -52,90 +120,381 @@ impl NodeContainer {
                 }))
                 .set_default(default.clone())
             }
-            RustSchemaKind::Tuple(rust_schema_or_refs) => todo!(),
-            RustSchemaKind::Map(rust_schema_or_ref) => todo!(),
-            RustSchemaKind::Struct(struct_) => {
-                fn get_struct_field_value<'a>(
-                    prev_default: &'a Value,
-                    struct_default: &'a Value,
-                    field_default: &'a Value,
-                    field_name: &'a str,
-                ) -> &'a Value {
-                    let prev_default = if let Some((_, map)) = prev_default.as_struct()
-                        && let Some(value) = map.0.get(field_name)
-                    {
-                        value
-                    } else {
-                        &Value::Empty
-                    };
-
-                    let struct_default = if let Some((_, map)) = struct_default.as_struct()
-                        && let Some(value) = map.0.get(field_name)
-                    {
-                        value
-                    } else {
-                        &Value::Empty
-                    };
+            RustSchemaKind::Tuple(rust_schema_or_refs) => NodeContainer::from_node(Node::Tuple(
+                Self::tuple_from_fields(root, rust_schema_or_refs, None, value, default, depth),
+            ))
+            .set_default(default.clone()),
+            RustSchemaKind::Map(rust_schema_or_ref) => {
+                let schema = root.resolve_schema(rust_schema_or_ref).unwrap();
 
-                    prev_default
-                        .if_not_empty(field_default)
-                        .if_not_empty(struct_default)
-                }
+                let value = value.as_map().map(|map| {
+                    map.iter()
+                        .map(|(key, v)| {
+                            (
+                                key.clone(),
+                                Self::from_schema_and_value_at_depth(
+                                    root,
+                                    schema,
+                                    v,
+                                    &Value::Empty,
+                                    depth + 1,
+                                )
+                                .set_is_removable(true),
+                            )
+                        })
+                        .collect()
+                });
 
+                NodeContainer::from_node(Node::Map(NodeMap { value })).set_default(default.clone())
+            }
+            // `struct A;`, but not `struct A {}`
+            RustSchemaKind::Struct(struct_) if struct_.unit => {
+                NodeContainer::from_node(Node::Unit(NodeUnit {
+                    name: Some(struct_.name.to_owned()),
+                }))
+                .set_name(Some(struct_.name.to_owned()))
+                .set_description(struct_.description.to_owned())
+                .set_default(default.clone())
+            }
+            RustSchemaKind::Struct(struct_) => {
                 let struct_default = struct_
                     .default
                     .as_ref()
                     .map(rust_schema_value_to_value)
                     .unwrap_or(Value::Empty);
 
-                NodeContainer::from_node(Node::Struct(NodeStruct {
-                    fields: struct_
-                        .fields
-                        .iter()
-                        .map(|(field_name, field)| {
-                            let schema = root.resolve_schema(&field.schema).unwrap();
-
-                            let field_default = field
-                                .default
-                                .as_ref()
-                                .map(rust_schema_value_to_value)
-                                .unwrap_or(Value::Empty);
-
-                            // we don't use the outer default because
-                            // we only want to detect when the default is defined on the struct definition
-                            let final_field_default = get_struct_field_value(
-                                &Value::Empty,
-                                &struct_default,
-                                &field_default,
-                                field_name,
-                            );
+                NodeContainer::from_node(Node::Struct(Self::struct_from_fields(
+                    root,
+                    &struct_.fields,
+                    value,
+                    &struct_default,
+                    depth,
+                )))
+                .set_name(Some(struct_.name.to_owned()))
+                .set_description(struct_.description.to_owned())
+                .set_default(default.if_not_empty(&struct_default).clone())
+            }
+            RustSchemaKind::TupleStruct(tuple_struct) => {
+                let tuple_struct_default = tuple_struct
+                    .default
+                    .as_ref()
+                    .map(rust_schema_value_to_value)
+                    .unwrap_or(Value::Empty);
 
-                            (
-                                field_name.to_owned(),
-                                Self::from_schema_and_value(
-                                    root,
-                                    schema,
-                                    get_struct_field_value(
-                                        value,
-                                        &struct_default,
-                                        &field_default,
-                                        field_name,
-                                    ),
-                                    final_field_default,
-                                )
-                                .set_name(Some(field_name.to_owned()))
-                                .set_description(field.description.to_owned())
-                                .set_default(final_field_default.clone()),
-                            )
+                let default = default.if_not_empty(&tuple_struct_default);
+
+                NodeContainer::from_node(Node::Tuple(Self::tuple_from_fields(
+                    root,
+                    &tuple_struct.fields,
+                    Some(tuple_struct.name.to_owned()),
+                    value,
+                    default,
+                    depth,
+                )))
+                .set_name(Some(tuple_struct.name.to_owned()))
+                .set_description(tuple_struct.description.to_owned())
+                .set_default(default.clone())
+            }
+            RustSchemaKind::Enum(enum_) => {
+                let pos = value
+                    .enum_variant_name()
+                    .and_then(|name| enum_.variants.iter().position(|v| v.name == name));
+
+                NodeContainer::from_node(Node::Enum(NodeEnum {
+                    value: pos,
+                    variants: enum_
+                        .variants
+                        .iter()
+                        .enumerate()
+                        .map(|(i, variant)| NodeEnumVariant {
+                            name: variant.name.to_owned(),
+                            description: variant.description.to_owned(),
+                            // only build the payload of the active variant,
+                            // the others are built when selected
+                            node: (pos == Some(i))
+                                .then(|| {
+                                    Self::enum_variant_at_depth(
+                                        root, variant, value, default, depth,
+                                    )
+                                })
+                                .flatten(),
                         })
                         .collect(),
                 }))
-                .set_name(Some(struct_.name.to_owned()))
-                .set_description(struct_.description.to_owned())
-                .set_default(default.if_not_empty(&struct_default).clone())
+                .set_name(Some(enum_.name.to_owned()))
+                .set_description(enum_.description.to_owned())
+                .set_default(default.clone())
+            }
+        }
+    }
+
+    /// Build the payload of an enum variant. Return [`None`] for unit variant.
+    ///
+    /// `value` and `default` are values of the enum. They are ignored if they
+    /// are not of this variant.
+    pub fn from_enum_variant(
+        root: &RustSchemaRoot,
+        variant: &EnumVariant,
+        value: &Value,
+        default: &Value,
+    ) -> Option<Self> {
+        Self::enum_variant_at_depth(root, variant, value, default, 0)
+    }
+
+    /// The payload itself is never lazy, because it has no schema.
+    fn enum_variant_at_depth(
+        root: &RustSchemaRoot,
+        variant: &EnumVariant,
+        value: &Value,
+        default: &Value,
+        depth: usize,
+    ) -> Option<Self> {
+        let of_variant = |value: &Value| match value.enum_variant_name() {
+            Some(name) if name == variant.name => value.clone(),
+            _ => Value::Empty,
+        };
+
+        let value = of_variant(value);
+        let default = of_variant(default);
+
+        let node = match &variant.kind {
+            EnumVariantKind::Unit => return None,
+            EnumVariantKind::Tuple(fields) => Node::Tuple(Self::tuple_from_fields(
+                root,
+                fields,
+                Some(variant.name.to_owned()),
+                &value,
+                &default,
+                depth,
+            )),
+            EnumVariantKind::Struct(fields) => Node::Struct(Self::struct_from_fields(
+                root, fields, &value, &default, depth,
+            )),
+        };
+
+        Some(
+            NodeContainer::from_node(node)
+                .set_name(Some(variant.name.to_owned()))
+                .set_description(variant.description.to_owned()),
+        )
+    }
+
+    /// Each slot takes its value at the same position in `value`,
+    /// falling back to the slot of `default`.
+    fn tuple_from_fields(
+        root: &RustSchemaRoot,
+        fields: &[RustSchemaOrRef],
+        name: Option<String>,
+        value: &Value,
+        default: &Value,
+        depth: usize,
+    ) -> NodeTuple {
+        fn tuple_values(value: &Value) -> Option<&Vec<Value>> {
+            match value {
+                Value::Tuple(values) | Value::TupleStruct(_, values) => Some(values),
+                _ => None,
             }
-            RustSchemaKind::TupleStruct(tuple_struct) => todo!(),
-            RustSchemaKind::Enum(_) => todo!(),
         }
+
+        let values = tuple_values(value);
+        let defaults = tuple_values(default);
+
+        NodeTuple {
+            name,
+            fields: fields
+                .iter()
+                .enumerate()
+                .map(|(i, field)| {
+                    let schema = root.resolve_schema(field).unwrap();
+
+                    let default = defaults
+                        .and_then(|values| values.get(i))
+                        .unwrap_or(&Value::Empty);
+
+                    Self::from_schema_and_value_at_depth(
+                        root,
+                        schema,
+                        values
+                            .and_then(|values| values.get(i))
+                            .unwrap_or(&Value::Empty)
+                            .if_not_empty(default),
+                        default,
+                        depth + 1,
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn struct_from_fields(
+        root: &RustSchemaRoot,
+        fields: &IndexMap<String, StructField>,
+        value: &Value,
+        struct_default: &Value,
+        depth: usize,
+    ) -> NodeStruct {
+        fn get_struct_field_value<'a>(
+            prev_default: &'a Value,
+            struct_default: &'a Value,
+            field_default: &'a Value,
+            field_name: &'a str,
+        ) -> &'a Value {
+            let prev_default = if let Some((_, map)) = prev_default.as_struct()
+                && let Some(value) = map.0.get(field_name)
+            {
+                value
+            } else {
+                &Value::Empty
+            };
+
+            let struct_default = if let Some((_, map)) = struct_default.as_struct()
+                && let Some(value) = map.0.get(field_name)
+            {
+                value
+            } else {
+                &Value::Empty
+            };
+
+            prev_default
+                .if_not_empty(field_default)
+                .if_not_empty(struct_default)
+        }
+
+        NodeStruct {
+            fields: fields
+                .iter()
+                .map(|(field_name, field)| {
+                    let schema = root.resolve_schema(&field.schema).unwrap();
+
+                    let field_default = field
+                        .default
+                        .as_ref()
+                        .map(rust_schema_value_to_value)
+                        .unwrap_or(Value::Empty);
+
+                    // we don't use the outer default because
+                    // we only want to detect when the default is defined on the struct definition
+                    let final_field_default = get_struct_field_value(
+                        &Value::Empty,
+                        struct_default,
+                        &field_default,
+                        field_name,
+                    );
+
+                    (
+                        field_name.to_owned(),
+                        Self::from_schema_and_value_at_depth(
+                            root,
+                            schema,
+                            get_struct_field_value(
+                                value,
+                                struct_default,
+                                &field_default,
+                                field_name,
+                            ),
+                            final_field_default,
+                            depth + 1,
+                        )
+                        .set_name(Some(field_name.to_owned()))
+                        .set_description(field.description.to_owned())
+                        .set_default(final_field_default.clone()),
+                    )
+                })
+                .collect(),
+            // filled by set_modified_from_value, to not copy the system config in the user one
+            unknown: IndexMap::new(),
+            removed_unknown: HashSet::new(),
+        }
+    }
+}
+
+/// Only nodes with children can be lazy, to always show leafs in the parent page.
+fn has_children(kind: &RustSchemaKind) -> bool {
+    match kind {
+        RustSchemaKind::Unit
+        | RustSchemaKind::Boolean
+        | RustSchemaKind::Number(_)
+        | RustSchemaKind::Char
+        | RustSchemaKind::String => false,
+        RustSchemaKind::Struct(struct_) => !struct_.fields.is_empty(),
+        RustSchemaKind::Option(_)
+        | RustSchemaKind::Array(_)
+        | RustSchemaKind::Tuple(_)
+        | RustSchemaKind::Map(_)
+        | RustSchemaKind::TupleStruct(_)
+        | RustSchemaKind::Enum(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_schema2::{RustSchema, RustSchemaTrait, schema_for};
+
+    use super::*;
+    use crate::{generic_value::Number, node::data_path::DataPathType};
+
+    #[derive(RustSchema)]
+    #[allow(dead_code)]
+    struct Pair(u8, String);
+
+    fn build<T: RustSchemaTrait>(value: &Value) -> NodeContainer {
+        let root = schema_for::<T>();
+        let schema = root.resolve_schema(&root.schema).unwrap();
+        let mut tree = NodeContainer::from_schema_and_value(&root, schema, value, &Value::Empty);
+        tree.set_modified_from_value(value);
+        tree
+    }
+
+    #[test]
+    fn tuple() {
+        let value = Value::Tuple(vec![Value::Number(Number::U8(1)), Value::Bool(true)]);
+        let tree = build::<(u8, bool)>(&value);
+
+        let Node::Tuple(node_tuple) = &tree.node else {
+            panic!("not a tuple: {tree:?}");
+        };
+        assert_eq!(node_tuple.name, None);
+        assert_eq!(node_tuple.fields.len(), 2);
+
+        let second = tree
+            .get_at(Box::new([DataPathType::Indice(1)].iter()))
+            .unwrap();
+        assert!(matches!(
+            second.node,
+            Node::Bool(NodeBool { value: Some(true) })
+        ));
+
+        assert_eq!(tree.to_value(), Some(value));
+    }
+
+    #[test]
+    fn tuple_struct() {
+        let value = Value::TupleStruct(
+            "Pair".to_string(),
+            vec![Value::Number(Number::U8(1)), Value::String("a".to_string())],
+        );
+        let tree = build::<Pair>(&value);
+
+        let Node::Tuple(node_tuple) = &tree.node else {
+            panic!("not a tuple: {tree:?}");
+        };
+        assert_eq!(node_tuple.name.as_deref(), Some("Pair"));
+        assert_eq!(tree.name.as_deref(), Some("Pair"));
+
+        let first = tree
+            .get_at(Box::new([DataPathType::Indice(0)].iter()))
+            .unwrap();
+        assert!(
+            matches!(&first.node, Node::Number(node_number) if node_number.value_string == "1")
+        );
+
+        assert_eq!(tree.to_value(), Some(value));
+
+        // a value of another shape leaves the fields unset
+        let tree = build::<Pair>(&Value::Bool(true));
+        assert!(
+            tree.get_at(Box::new([DataPathType::Indice(1)].iter()))
+                .is_some_and(|node| matches!(&node.node, Node::String(NodeString { value: None })))
+        );
     }
 }
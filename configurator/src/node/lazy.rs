@@ -0,0 +1,205 @@
+use std::iter;
+
+use rust_schema2::RustSchemaRoot;
+
+use crate::node::{
+    Node, NodeContainer,
+    data_path::{self, DataPathType},
+    schema_at,
+};
+
+impl NodeContainer {
+    /// Build the lazy nodes on `data_path` and their direct children,
+    /// so every page of this path can be shown.
+    ///
+    /// Stop at the first node that doesn't exist.
+    pub fn build_lazy(&mut self, root: &RustSchemaRoot, data_path: &[DataPathType]) {
+        let mut node = self;
+
+        for pos in 0..=data_path.len() {
+            let current_path = &data_path[..pos];
+
+            node.build_if_lazy(root, current_path);
+
+            for child in node.children_data_path() {
+                if let Some(child_node) = node.get_at_mut(Box::new(iter::once(&child))) {
+                    child_node.build_if_lazy(root, &data_path::push_one(current_path, child));
+                }
+            }
+
+            let Some(data) = data_path.get(pos) else {
+                break;
+            };
+
+            match node.get_at_mut(Box::new(iter::once(data))) {
+                Some(n) => node = n,
+                None => break,
+            }
+        }
+    }
+
+    pub(super) fn build_if_lazy(&mut self, root: &RustSchemaRoot, data_path: &[DataPathType]) {
+        let Node::Lazy(node_lazy) = &self.node else {
+            return;
+        };
+
+        let schema = match schema_at(root, data_path) {
+            Ok(schema) => schema,
+            Err(e) => {
+                error!("can't build lazy node: {e}");
+                return;
+            }
+        };
+
+        let mut built =
+            NodeContainer::from_schema_and_value(root, schema, &node_lazy.value, &self.default);
+
+        built.set_modified_from_value(&node_lazy.modified_value);
+
+        // the parent could have changed the name and the description
+        self.name = self.name.take().or(built.name);
+        self.description = self.description.take().or(built.description);
+        self.default = built.default;
+        self.modified = built.modified;
+        self.node = built.node;
+    }
+
+    /// Children that can be reached with one more element of path.
+    ///
+    /// The payload of an enum is excluded, because it is never lazy.
+    fn children_data_path(&self) -> Vec<DataPathType> {
+        match &self.node {
+            Node::Array(node_array) => node_array
+                .value
+                .iter()
+                .flat_map(|values| (0..values.len()).map(DataPathType::Indice))
+                .collect(),
+            Node::Map(node_map) => node_map
+                .value
+                .iter()
+                .flat_map(|entries| entries.keys().cloned().map(DataPathType::Name))
+                .collect(),
+            Node::Struct(node_struct) => node_struct
+                .fields
+                .keys()
+                .cloned()
+                .map(DataPathType::Name)
+                .collect(),
+            Node::Tuple(node_tuple) => (0..node_tuple.fields.len())
+                .map(DataPathType::Indice)
+                .collect(),
+            Node::Option(node_option) if node_option.some_node().is_some() => {
+                vec![DataPathType::Indice(0)]
+            }
+            _ => vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_schema2::{RustSchema, schema_for};
+
+    use super::*;
+    use crate::{
+        generic_value::Value,
+        node::{NodeLazy, validation::ValidationIssueKind},
+        providers::cosmic_ron::ron_value_to_value,
+    };
+
+    #[derive(RustSchema)]
+    #[allow(dead_code)]
+    struct Rec {
+        n: u8,
+        next: Option<Box<Rec>>,
+    }
+
+    fn rec_value(ron: &str) -> Value {
+        ron_value_to_value(ron_value::from_str(ron).unwrap()).unwrap()
+    }
+
+    fn rec_tree(root: &RustSchemaRoot, value: &Value) -> NodeContainer {
+        let schema = root.resolve_schema(&root.schema).unwrap();
+        let mut tree = NodeContainer::from_schema_and_value(root, schema, value, &Value::Empty);
+        tree.set_modified_from_value(value);
+        tree
+    }
+
+    /// `next/Some/next/Some`, the first node deeper than the max depth
+    fn lazy_path() -> Vec<DataPathType> {
+        vec![
+            DataPathType::Name("next".to_string()),
+            DataPathType::Indice(0),
+            DataPathType::Name("next".to_string()),
+            DataPathType::Indice(0),
+        ]
+    }
+
+    const VALUE: &str = "Rec(n: 1, next: Some(Rec(n: 2, next: Some(Rec(n: 3, next: None)))))";
+
+    #[test]
+    fn recursive_schema() {
+        let root = schema_for::<Rec>();
+        let value = rec_value(VALUE);
+        let mut tree = rec_tree(&root, &value);
+
+        let path = lazy_path();
+        let node = tree.get_at(Box::new(path.iter())).unwrap();
+        assert!(matches!(node.node, Node::Lazy(_)));
+        assert!(node.modified);
+        assert!(tree.to_value().unwrap().equivalent(&value));
+
+        tree.build_lazy(&root, &path);
+
+        let node = tree.get_at(Box::new(path.iter())).unwrap();
+        assert!(matches!(node.node, Node::Struct(_)));
+        assert!(node.modified);
+        assert!(tree.to_value().unwrap().equivalent(&value));
+    }
+
+    #[test]
+    fn build_recomputes_modified() {
+        let root = schema_for::<Rec>();
+        let value = rec_value(VALUE);
+
+        let lazy = |modified_value: &Value, modified: bool| {
+            NodeContainer::from_node(Node::Lazy(NodeLazy {
+                value: value.clone(),
+                modified_value: modified_value.clone(),
+            }))
+            .set_is_modified(modified)
+        };
+
+        // only the system config has a value
+        let mut node = lazy(&Value::Empty, true);
+        node.build_if_lazy(&root, &[]);
+        assert!(matches!(node.node, Node::Struct(_)));
+        assert!(!node.modified);
+        assert_eq!(node.to_value(), None);
+
+        let mut node = lazy(&value, false);
+        node.build_if_lazy(&root, &[]);
+        assert!(node.modified);
+        assert!(node.to_value().unwrap().equivalent(&value));
+    }
+
+    #[test]
+    fn validate_lazy() {
+        let root = schema_for::<Rec>();
+        let value =
+            rec_value("Rec(n: 1, next: Some(Rec(n: 2, next: Some(Rec(n: 300, next: None)))))");
+        let tree = rec_tree(&root, &value);
+
+        let issues = tree.validate(&root);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, ValidationIssueKind::InvalidNumber);
+        assert_eq!(
+            issues[0].data_path,
+            data_path::push_one(&lazy_path(), DataPathType::Name("n".to_string()))
+        );
+
+        // the tree is not built by the validation
+        let node = tree.get_at(Box::new(lazy_path().iter())).unwrap();
+        assert!(matches!(node.node, Node::Lazy(_)));
+    }
+}
@@ -8,7 +8,9 @@ use std::{
 use derive_more::derive::Unwrap;
 use indexmap::IndexMap;
 use light_enum::LightEnum;
-use rust_schema2::{NumberKind, RustSchema, RustSchemaKind, RustSchemaOrRef, RustSchemaRoot};
+use rust_schema2::{
+    EnumVariantKind, NumberKind, RustSchema, RustSchemaKind, RustSchemaOrRef, RustSchemaRoot,
+};
 
 use crate::{
     generic_value::{F32, F64, Map, Number, Value},
@@ -22,10 +24,17 @@ pub mod data_path;
 // mod tests;
 
 mod from_schema_and_value;
+mod lazy;
+mod number;
+mod pending_input;
 mod set_modified;
 mod to_value;
+mod validation;
+
+pub use pending_input::PendingInput;
+pub use validation::{ValidationIssue, ValidationIssueKind};
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NodeContainer {
     pub name: Option<String>,
     pub description: Option<String>,
@@ -35,25 +44,167 @@ pub struct NodeContainer {
     pub node: Node,
 }
 
-#[derive(Debug, Unwrap)]
+#[derive(Debug, Clone, Unwrap)]
 #[unwrap(ref_mut)]
 pub enum Node {
+    Unit(NodeUnit),
+    Bool(NodeBool),
+    Number(NodeNumber),
+    Char(NodeChar),
     String(NodeString),
     Array(NodeArray),
+    Map(NodeMap),
     Struct(NodeStruct),
+    Tuple(NodeTuple),
+    Enum(NodeEnum),
+    Option(NodeOption),
+    Lazy(NodeLazy),
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeBool {
+    pub value: Option<bool>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub struct NodeNumber {
+    pub kind: NumberKind,
+    pub value: Option<Number>,
+    /// Raw text of the input, can be invalid
+    pub value_string: String,
+}
+
+#[derive(Debug, Clone)]
 pub struct NodeString {
     pub value: Option<String>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct NodeStruct {
     pub fields: IndexMap<String, NodeContainer>,
+    /// Keys of the user config that the schema doesn't describe,
+    /// written back unchanged, so a newer config is not lost
+    pub unknown: IndexMap<String, Value>,
+    /// Unknown keys removed by the user, which must be removed from the
+    /// config. The others are kept, even when this struct is reset.
+    pub removed_unknown: HashSet<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeTuple {
+    /// Name of the tuple struct or of the enum variant
+    pub name: Option<String>,
+    pub fields: Vec<NodeContainer>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeEnum {
+    /// Position of the active variant
+    pub value: Option<usize>,
+    pub variants: Vec<NodeEnumVariant>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeEnumVariant {
+    pub name: String,
+    pub description: Option<String>,
+    /// Payload of the variant. Always [`None`] for unit variants.
+    /// We keep it when switching to another variant, to not lose the data.
+    pub node: Option<NodeContainer>,
 }
 
-#[derive(Debug)]
+impl NodeEnum {
+    pub fn active_node(&self) -> Option<&NodeContainer> {
+        self.value
+            .and_then(|pos| self.variants.get(pos))
+            .and_then(|variant| variant.node.as_ref())
+    }
+
+    pub fn active_node_mut(&mut self) -> Option<&mut NodeContainer> {
+        self.value
+            .and_then(|pos| self.variants.get_mut(pos))
+            .and_then(|variant| variant.node.as_mut())
+    }
+}
+
+/// A subtree which is not built yet, to support recursive and very large schemas.
+/// It is built when opened, see [`NodeContainer::build_lazy`].
+#[derive(Debug, Clone)]
+pub struct NodeLazy {
+    /// Value used to build the subtree
+    pub value: Value,
+    /// Value written while the subtree is not built
+    pub modified_value: Value,
+}
+
+/// Unit and unit struct. They have only one possible value.
+#[derive(Debug, Clone)]
+pub struct NodeUnit {
+    /// Name of the unit struct
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeChar {
+    pub value: Option<char>,
+    /// Text of the input, which can be invalid
+    pub value_string: String,
+}
+
+impl NodeChar {
+    pub fn new(value: Option<char>) -> Self {
+        Self {
+            value,
+            value_string: value.map(String::from).unwrap_or_default(),
+        }
+    }
+
+    pub fn try_parse_from_str(&self, value: &str) -> anyhow::Result<char> {
+        let mut chars = value.chars();
+
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => Ok(c),
+            _ => bail!("{:?} is not a single character", value),
+        }
+    }
+
+    /// Same as [`NodeNumber::set_from_str`]
+    pub fn set_from_str(&mut self, value: String) -> anyhow::Result<()> {
+        let parsed = self.try_parse_from_str(&value);
+        self.value_string = value;
+        self.value = parsed.as_ref().ok().copied();
+        parsed.map(|_| ())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NodeOption {
+    /// [`None`] if no value is defined
+    pub is_some: Option<bool>,
+    /// Only built when the option is `Some`, to support recursive types.
+    /// We keep it when switching to `None`, to not lose the data.
+    pub node: Option<Box<NodeContainer>>,
+}
+
+impl NodeOption {
+    pub fn some_node(&self) -> Option<&NodeContainer> {
+        if self.is_some == Some(true) {
+            self.node.as_deref()
+        } else {
+            None
+        }
+    }
+
+    pub fn some_node_mut(&mut self) -> Option<&mut NodeContainer> {
+        if self.is_some == Some(true) {
+            self.node.as_deref_mut()
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct NodeArray {
     pub min: Option<u64>,
     pub max: Option<u64>,
@@ -61,6 +212,50 @@ pub struct NodeArray {
     pub has_template: bool,
 }
 
+#[derive(Debug, Clone)]
+pub struct NodeMap {
+    /// [`None`] if no value is defined
+    pub value: Option<IndexMap<String, NodeContainer>>,
+}
+
+impl NodeMap {
+    /// Insert a new entry at the end. Return false if the key is already used.
+    pub fn insert(&mut self, key: String, node: NodeContainer) -> bool {
+        let entries = self.value.get_or_insert_with(IndexMap::new);
+
+        if entries.contains_key(&key) {
+            return false;
+        }
+
+        entries.insert(key, node);
+        true
+    }
+
+    /// Rename an entry, keeping its position.
+    /// Return false if `prev` doesn't exist or `new` is already used.
+    pub fn rename(&mut self, prev: &str, new: String) -> bool {
+        let Some(entries) = &mut self.value else {
+            return false;
+        };
+
+        if entries.contains_key(&new) {
+            return false;
+        }
+
+        match entries.shift_remove_full(prev) {
+            Some((pos, _, node)) => {
+                entries.shift_insert(pos, new, node);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn remove(&mut self, key: &str) -> Option<NodeContainer> {
+        self.value.as_mut()?.shift_remove(key)
+    }
+}
+
 impl NodeContainer {
     pub fn from_node(node: Node) -> Self {
         Self {
@@ -102,6 +297,18 @@ impl NodeContainer {
     pub fn remove_value_rec(&mut self) {
         self.modified = false;
         match &mut self.node {
+            Node::Unit(_) => {}
+            Node::Bool(node_bool) => {
+                node_bool.value.take();
+            }
+            Node::Number(node_number) => {
+                node_number.value.take();
+                node_number.value_string.clear();
+            }
+            Node::Char(node_char) => {
+                node_char.value.take();
+                node_char.value_string.clear();
+            }
             Node::String(node_string) => {
                 node_string.value.take();
             }
@@ -114,28 +321,35 @@ impl NodeContainer {
             Node::Array(node_array) => {
                 node_array.value.take();
             }
+            Node::Map(node_map) => {
+                node_map.value.take();
+            }
+            Node::Tuple(node_tuple) => {
+                node_tuple
+                    .fields
+                    .iter_mut()
+                    .for_each(|field| field.remove_value_rec());
+            }
+            Node::Enum(node_enum) => {
+                node_enum.value.take();
+                node_enum
+                    .variants
+                    .iter_mut()
+                    .filter_map(|variant| variant.node.as_mut())
+                    .for_each(|node| node.remove_value_rec());
+            }
+            Node::Option(node_option) => {
+                node_option.is_some.take();
+                if let Some(node) = &mut node_option.node {
+                    node.remove_value_rec();
+                }
+            }
+            Node::Lazy(node_lazy) => {
+                node_lazy.value = Value::Empty;
+                node_lazy.modified_value = Value::Empty;
+            }
         };
     }
-
-    /// Return true if all active nodes have a value
-    pub fn is_valid(&self) -> bool {
-        match &self.node {
-            Node::String(node_string) => node_string.value.is_some(),
-            Node::Struct(node_struct) => node_struct.fields.values().all(|f| f.is_valid()),
-            Node::Array(node_array) => node_array.value.as_ref().is_some_and(|values| {
-                let is_complete = node_array
-                    .min
-                    .map(|min| values.len() >= min as usize)
-                    .unwrap_or(true)
-                    && node_array
-                        .max
-                        .map(|max| values.len() <= max as usize)
-                        .unwrap_or(true);
-
-                is_complete && values.iter().all(|n| n.is_valid())
-            }),
-        }
-    }
 }
 
 pub fn schema_at<'a>(
@@ -144,18 +358,30 @@ pub fn schema_at<'a>(
 ) -> anyhow::Result<&'a RustSchema> {
     let mut schema = root.resolve_schema(&root.schema)?;
 
-    for data in data_path {
+    let mut data_path = data_path.iter();
+
+    while let Some(data) = data_path.next() {
         match (&schema.kind, data) {
-            (RustSchemaKind::Option(rust_schema_or_ref), DataPathType::Name(_)) => todo!(),
-            (RustSchemaKind::Option(rust_schema_or_ref), DataPathType::Indice(_)) => todo!(),
+            (RustSchemaKind::Option(rust_schema_or_ref), DataPathType::Indice(0)) => {
+                schema = root.resolve_schema(rust_schema_or_ref)?;
+            }
             (RustSchemaKind::Array(array), DataPathType::Indice(_)) => match &array.template {
                 Some(kind) => {
                     schema = root.resolve_schema(kind)?;
                 }
                 None => bail!("no kind for array: {:?}", schema),
             },
-            (RustSchemaKind::Tuple(rust_schema_or_refs), DataPathType::Indice(_)) => todo!(),
-            (RustSchemaKind::Map(rust_schema_or_ref), DataPathType::Name(_)) => todo!(),
+            (RustSchemaKind::Tuple(rust_schema_or_refs), DataPathType::Indice(i)) => {
+                match rust_schema_or_refs.get(*i) {
+                    Some(field) => {
+                        schema = root.resolve_schema(field)?;
+                    }
+                    None => bail!("no field at {} in tuple", i),
+                }
+            }
+            (RustSchemaKind::Map(rust_schema_or_ref), DataPathType::Name(_)) => {
+                schema = root.resolve_schema(rust_schema_or_ref)?;
+            }
             (RustSchemaKind::Struct(struct_), DataPathType::Name(name)) => {
                 match struct_.fields.get(name) {
                     Some(field) => {
@@ -166,9 +392,44 @@ pub fn schema_at<'a>(
                     }
                 }
             }
-            (RustSchemaKind::TupleStruct(tuple_struct), DataPathType::Indice(_)) => todo!(),
-            (RustSchemaKind::Enum(_), DataPathType::Name(_)) => todo!(),
-            (RustSchemaKind::Enum(_), DataPathType::Indice(_)) => todo!(),
+            (RustSchemaKind::TupleStruct(tuple_struct), DataPathType::Indice(i)) => {
+                match tuple_struct.fields.get(*i) {
+                    Some(field) => {
+                        schema = root.resolve_schema(field)?;
+                    }
+                    None => bail!("no field at {} in {}", i, tuple_struct.name),
+                }
+            }
+            // the payload of a variant has no schema,
+            // so we resolve the variant and its field at the same time
+            (RustSchemaKind::Enum(enum_), DataPathType::Indice(pos)) => {
+                let Some(variant) = enum_.variants.get(*pos) else {
+                    bail!("no variant at {} in {}", pos, enum_.name)
+                };
+
+                let Some(data) = data_path.next() else {
+                    bail!("variant {} of {} has no schema", variant.name, enum_.name)
+                };
+
+                match (&variant.kind, data) {
+                    (EnumVariantKind::Tuple(fields), DataPathType::Indice(i))
+                        if let Some(field) = fields.get(*i) =>
+                    {
+                        schema = root.resolve_schema(field)?;
+                    }
+                    (EnumVariantKind::Struct(fields), DataPathType::Name(name))
+                        if let Some(field) = fields.get(name) =>
+                    {
+                        schema = root.resolve_schema(&field.schema)?;
+                    }
+                    _ => bail!(
+                        "variant {} of {} has no field {}",
+                        variant.name,
+                        enum_.name,
+                        data
+                    ),
+                }
+            }
             _ => bail!("schema {:?} is not compatible with {}", schema, data),
         }
     }
@@ -181,14 +442,15 @@ fn value_at<'a>(value: &'a Value, data_path: &[DataPathType]) -> &'a Value {
 
     for data in data_path {
         value = match (value, data) {
-            (Value::Option(value), DataPathType::Name(_)) => todo!(),
-            (Value::Option(value), DataPathType::Indice(_)) => todo!(),
+            (Value::Option(Some(value)), DataPathType::Indice(0)) => value,
             (Value::Array(values), DataPathType::Name(_)) => todo!(),
             (Value::Array(values), DataPathType::Indice(_)) => todo!(),
-            (Value::Map(map), DataPathType::Name(_)) => todo!(),
-            (Value::Map(map), DataPathType::Indice(_)) => todo!(),
-            (Value::Tuple(values), DataPathType::Name(_)) => todo!(),
-            (Value::Tuple(values), DataPathType::Indice(_)) => todo!(),
+            (Value::Map(map), DataPathType::Name(name)) if let Some(value) = map.0.get(name) => {
+                value
+            }
+            (Value::Tuple(values), DataPathType::Indice(i)) if let Some(value) = values.get(*i) => {
+                value
+            }
             (Value::UnitStruct(_), DataPathType::Name(_)) => todo!(),
             (Value::UnitStruct(_), DataPathType::Indice(_)) => todo!(),
             (Value::Struct(_, map), DataPathType::Name(name))
@@ -255,8 +517,17 @@ fn rust_schema_value_to_value(value: &rust_schema2::Value) -> Value {
             name.to_owned(),
             values.iter().map(rust_schema_value_to_value).collect(),
         ),
-        rust_schema2::Value::EnumVariantUnit(_) => todo!(),
-        rust_schema2::Value::EnumVariantTuple(_, values) => todo!(),
-        rust_schema2::Value::EnumVariantStruct(_, btree_map) => todo!(),
+        rust_schema2::Value::EnumVariantUnit(name) => Value::UnitStruct(name.to_owned()),
+        rust_schema2::Value::EnumVariantTuple(name, values) => Value::TupleStruct(
+            name.to_owned(),
+            values.iter().map(rust_schema_value_to_value).collect(),
+        ),
+        rust_schema2::Value::EnumVariantStruct(name, btree_map) => Value::Struct(
+            Some(name.to_owned()),
+            btree_map
+                .iter()
+                .map(|(k, v)| (k.to_owned(), rust_schema_value_to_value(v)))
+                .collect(),
+        ),
     }
 }
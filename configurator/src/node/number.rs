@@ -0,0 +1,189 @@
+use std::num::IntErrorKind;
+
+use anyhow::bail;
+use rust_schema2::NumberKind;
+
+use crate::{
+    generic_value::{F32, F64, Number},
+    node::NodeNumber,
+};
+
+impl NodeNumber {
+    pub fn new(kind: NumberKind, value: Option<&Number>) -> Self {
+        let value_string = value.map(|v| v.to_string()).unwrap_or_default();
+
+        // numbers read from a file don't have the kind of the schema
+        // (ex: 1 is parsed as an u128), so we convert them
+        let value = value.and_then(|_| parse_number(&kind, &value_string).ok());
+
+        Self {
+            kind,
+            value,
+            value_string,
+        }
+    }
+
+    pub fn try_parse_from_str(&self, value: &str) -> anyhow::Result<Number> {
+        parse_number(&self.kind, value)
+    }
+
+    /// Set the text of the input. The value is unset if the text can't be
+    /// parsed, so a previous number is not written. See the validation for
+    /// the issue of an invalid text.
+    pub fn set_from_str(&mut self, value: String) -> anyhow::Result<()> {
+        let parsed = self.try_parse_from_str(&value);
+        self.value_string = value;
+        self.value = parsed.as_ref().ok().cloned();
+        parsed.map(|_| ())
+    }
+}
+
+fn parse_number(kind: &NumberKind, value: &str) -> anyhow::Result<Number> {
+    let value = value.trim();
+
+    macro_rules! parse_int {
+        ($variant:ident, $ty:ty) => {
+            match value.parse::<$ty>() {
+                Ok(v) => Number::$variant(v),
+                Err(e) => match e.kind() {
+                    IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => bail!(
+                        "{} is out of range for {:?} ({}..={})",
+                        value,
+                        kind,
+                        <$ty>::MIN,
+                        <$ty>::MAX
+                    ),
+                    _ => bail!("{:?} is not a valid {:?}", value, kind),
+                },
+            }
+        };
+    }
+
+    macro_rules! parse_float {
+        ($variant:ident, $wrap:ident, $ty:ty) => {
+            match value.parse::<$ty>() {
+                // inf is a valid input, but 1e40 for an f32 is not
+                Ok(v) if v.is_infinite() && !value.to_lowercase().contains("inf") => {
+                    bail!("{} is out of range for {:?}", value, kind)
+                }
+                Ok(v) => Number::$variant($wrap(v)),
+                Err(_) => bail!("{:?} is not a valid {:?}", value, kind),
+            }
+        };
+    }
+
+    let number = match kind {
+        NumberKind::U8 => parse_int!(U8, u8),
+        NumberKind::U16 => parse_int!(U16, u16),
+        NumberKind::U32 => parse_int!(U32, u32),
+        NumberKind::U64 => parse_int!(U64, u64),
+        NumberKind::U128 => parse_int!(U128, u128),
+        NumberKind::USize => parse_int!(USize, usize),
+        NumberKind::I8 => parse_int!(I8, i8),
+        NumberKind::I16 => parse_int!(I16, i16),
+        NumberKind::I32 => parse_int!(I32, i32),
+        NumberKind::I64 => parse_int!(I64, i64),
+        NumberKind::I128 => parse_int!(I128, i128),
+        NumberKind::ISize => parse_int!(ISize, isize),
+        NumberKind::F32 => parse_float!(F32, F32, f32),
+        NumberKind::F64 => parse_float!(F64, F64, f64),
+    };
+
+    Ok(number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        generic_value::Value,
+        node::{Node, NodeContainer, validation::ValidationIssueKind},
+    };
+    use rust_schema2::schema_for;
+
+    fn number_container(value: Number) -> NodeContainer {
+        NodeContainer::from_node(Node::Number(NodeNumber::new(NumberKind::U8, Some(&value))))
+            .set_is_modified(true)
+    }
+
+    #[test]
+    fn parse_integer_range() {
+        assert_eq!(
+            parse_number(&NumberKind::U8, "255").unwrap(),
+            Number::U8(255)
+        );
+        assert!(parse_number(&NumberKind::U8, "300").is_err());
+        assert!(parse_number(&NumberKind::U32, "-1").is_err());
+        assert_eq!(
+            parse_number(&NumberKind::I8, "-128").unwrap(),
+            Number::I8(-128)
+        );
+        assert!(parse_number(&NumberKind::I32, "1.5").is_err());
+    }
+
+    #[test]
+    fn parse_float() {
+        assert_eq!(
+            parse_number(&NumberKind::F64, "1e3").unwrap(),
+            Number::F64(F64(1000.0))
+        );
+        assert_eq!(
+            parse_number(&NumberKind::F32, "-2.5E-1").unwrap(),
+            Number::F32(F32(-0.25))
+        );
+        assert!(parse_number(&NumberKind::F32, "1e40").is_err());
+        assert!(parse_number(&NumberKind::F32, "inf").is_ok());
+        assert!(parse_number(&NumberKind::F64, "abc").is_err());
+    }
+
+    #[test]
+    fn convert_read_number() {
+        let node = NodeNumber::new(NumberKind::U32, Some(&Number::U128(200)));
+        assert_eq!(node.value, Some(Number::U32(200)));
+
+        let node = NodeNumber::new(NumberKind::U8, Some(&Number::U128(300)));
+        assert_eq!(node.value, None);
+        assert_eq!(node.value_string, "300");
+    }
+
+    #[test]
+    fn clear_input() {
+        let mut node = number_container(Number::U8(1));
+
+        assert!(
+            node.node
+                .unwrap_number_mut()
+                .set_from_str(String::new())
+                .is_err()
+        );
+
+        assert_eq!(node.to_value(), None);
+        let issues = node.validate(&schema_for::<u8>());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, ValidationIssueKind::MissingValue);
+    }
+
+    #[test]
+    fn invalid_input() {
+        let mut node = number_container(Number::U8(1));
+
+        assert!(
+            node.node
+                .unwrap_number_mut()
+                .set_from_str("300".to_string())
+                .is_err()
+        );
+
+        assert_eq!(node.to_value(), None);
+        let issues = node.validate(&schema_for::<u8>());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, ValidationIssueKind::InvalidNumber);
+
+        node.node
+            .unwrap_number_mut()
+            .set_from_str("2".to_string())
+            .unwrap();
+        assert_eq!(node.to_value(), Some(Value::Number(Number::U8(2))));
+        assert!(node.validate(&schema_for::<u8>()).is_empty());
+    }
+}
@@ -0,0 +1,107 @@
+use crate::node::{Node, NodeContainer, data_path::DataPathType};
+
+/// Text of an input which is not the value of its node,
+/// because it can't be parsed. It is not in the config,
+/// so it would be lost when the tree is built again.
+#[derive(Debug)]
+pub struct PendingInput {
+    data_path: Vec<DataPathType>,
+    text: String,
+}
+
+impl NodeContainer {
+    pub fn pending_inputs(&self) -> Vec<PendingInput> {
+        let mut inputs = Vec::new();
+        self.pending_inputs_rec(&mut Vec::new(), &mut inputs);
+        inputs
+    }
+
+    fn pending_inputs_rec(
+        &self,
+        data_path: &mut Vec<DataPathType>,
+        inputs: &mut Vec<PendingInput>,
+    ) {
+        fn child(
+            data_path: &mut Vec<DataPathType>,
+            data: DataPathType,
+            node: &NodeContainer,
+            inputs: &mut Vec<PendingInput>,
+        ) {
+            data_path.push(data);
+            node.pending_inputs_rec(data_path, inputs);
+            data_path.pop();
+        }
+
+        let mut push = |text: &String| {
+            inputs.push(PendingInput {
+                data_path: data_path.clone(),
+                text: text.clone(),
+            })
+        };
+
+        match &self.node {
+            Node::Number(node_number) => {
+                if node_number
+                    .try_parse_from_str(&node_number.value_string)
+                    .ok()
+                    != node_number.value
+                {
+                    push(&node_number.value_string);
+                }
+            }
+            Node::Char(node_char) => {
+                if node_char.try_parse_from_str(&node_char.value_string).ok() != node_char.value {
+                    push(&node_char.value_string);
+                }
+            }
+            Node::Struct(node_struct) => {
+                for (name, field) in &node_struct.fields {
+                    child(data_path, DataPathType::Name(name.clone()), field, inputs);
+                }
+            }
+            Node::Array(node_array) => {
+                for (pos, node) in node_array.value.iter().flatten().enumerate() {
+                    child(data_path, DataPathType::Indice(pos), node, inputs);
+                }
+            }
+            Node::Map(node_map) => {
+                for (key, node) in node_map.value.iter().flatten() {
+                    child(data_path, DataPathType::Name(key.clone()), node, inputs);
+                }
+            }
+            Node::Tuple(node_tuple) => {
+                for (pos, field) in node_tuple.fields.iter().enumerate() {
+                    child(data_path, DataPathType::Indice(pos), field, inputs);
+                }
+            }
+            Node::Enum(node_enum) => {
+                if let Some(pos) = node_enum.value
+                    && let Some(node) = node_enum.active_node()
+                {
+                    child(data_path, DataPathType::Indice(pos), node, inputs);
+                }
+            }
+            Node::Option(node_option) => {
+                if let Some(node) = node_option.some_node() {
+                    child(data_path, DataPathType::Indice(0), node, inputs);
+                }
+            }
+            Node::Unit(_) | Node::Bool(_) | Node::String(_) | Node::Lazy(_) => {}
+        }
+    }
+
+    /// Put back the inputs whose node still exists
+    pub fn restore_pending_inputs(&mut self, inputs: Vec<PendingInput>) {
+        for input in inputs {
+            let Some(node) = self.get_at_mut(Box::new(input.data_path.iter())) else {
+                continue;
+            };
+
+            match &mut node.node {
+                Node::Number(node_number) => node_number.value_string = input.text,
+                Node::Char(node_char) => node_char.value_string = input.text,
+                _ => {}
+            }
+        }
+    }
+}
@@ -26,6 +26,38 @@ impl NodeContainer {
                         panic!()
                     }
                 }
+                (Node::Map(node_map), DataPathType::Name(key)) => {
+                    if let Some(value) = &mut node_map.value
+                        && let Some(n) = value.get_mut(key)
+                    {
+                        node = n;
+                    } else {
+                        panic!()
+                    }
+                }
+                (Node::Tuple(node_tuple), DataPathType::Indice(pos)) => {
+                    if let Some(field) = node_tuple.fields.get_mut(*pos) {
+                        node = field;
+                    } else {
+                        panic!()
+                    }
+                }
+                (Node::Enum(node_enum), DataPathType::Indice(pos))
+                    if node_enum.value == Some(*pos) =>
+                {
+                    if let Some(n) = node_enum.active_node_mut() {
+                        node = n;
+                    } else {
+                        panic!()
+                    }
+                }
+                (Node::Option(node_option), DataPathType::Indice(0)) => {
+                    if let Some(n) = node_option.some_node_mut() {
+                        node = n;
+                    } else {
+                        panic!()
+                    }
+                }
                 _ => panic!(),
             }
         }
@@ -42,6 +74,10 @@ impl NodeContainer {
             node.modified = true;
 
             match &mut node.node {
+                Node::Unit(_) => {}
+                Node::Bool(node_bool) => {}
+                Node::Number(node_number) => {}
+                Node::Char(_) => {}
                 Node::String(node_string) => {}
                 Node::Array(node_array) => {
                     if let Some(value) = &mut node_array.value {
@@ -50,6 +86,13 @@ impl NodeContainer {
                         }
                     }
                 }
+                Node::Map(node_map) => {
+                    if let Some(value) = &mut node_map.value {
+                        for node in value.values_mut() {
+                            inner(node, std::iter::empty(), true);
+                        }
+                    }
+                }
                 Node::Struct(node_struct) => {
                     if force {
                         for field in &mut node_struct.fields {
@@ -64,6 +107,37 @@ impl NodeContainer {
                         }
                     }
                 }
+                Node::Tuple(node_tuple) => {
+                    if force {
+                        for field in &mut node_tuple.fields {
+                            inner(field, std::iter::empty(), true);
+                        }
+                    } else {
+                        if let Some(data) = data_path.next() {
+                            let data = data.as_indice().unwrap();
+                            let field = node_tuple.fields.get_mut(data).unwrap();
+
+                            inner(field, data_path, false);
+                        }
+                    }
+                }
+                Node::Enum(node_enum) => {
+                    // the payload of a variant is always written entirely
+                    if let Some(node) = node_enum.active_node_mut() {
+                        inner(node, std::iter::empty(), true);
+                    }
+                }
+                Node::Option(node_option) => {
+                    // we need the full value to write Some(..)
+                    if let Some(node) = node_option.some_node_mut() {
+                        inner(node, std::iter::empty(), true);
+                    }
+                }
+                Node::Lazy(node_lazy) => {
+                    if force {
+                        node_lazy.modified_value = node_lazy.value.clone();
+                    }
+                }
             }
         }
 
@@ -74,6 +148,10 @@ impl NodeContainer {
         self.modified = false;
 
         match &mut self.node {
+            Node::Unit(_) => {}
+            Node::Bool(node_bool) => {}
+            Node::Number(node_number) => {}
+            Node::Char(_) => {}
             Node::String(node_string) => {}
             Node::Array(node_array) => {
                 if let Some(value) = &mut node_array.value {
@@ -82,16 +160,52 @@ impl NodeContainer {
                     }
                 }
             }
+            Node::Map(node_map) => {
+                if let Some(value) = &mut node_map.value {
+                    for node in value.values_mut() {
+                        node.set_unmodified();
+                    }
+                }
+            }
             Node::Struct(node_struct) => {
                 for (_, field) in &mut node_struct.fields {
                     field.set_unmodified();
                 }
+                node_struct.unknown.clear();
+            }
+            Node::Tuple(node_tuple) => {
+                for field in &mut node_tuple.fields {
+                    field.set_unmodified();
+                }
+            }
+            Node::Enum(node_enum) => {
+                for node in node_enum
+                    .variants
+                    .iter_mut()
+                    .filter_map(|variant| variant.node.as_mut())
+                {
+                    node.set_unmodified();
+                }
+            }
+            Node::Option(node_option) => {
+                if let Some(node) = &mut node_option.node {
+                    node.set_unmodified();
+                }
+            }
+            Node::Lazy(node_lazy) => {
+                node_lazy.modified_value = Value::Empty;
             }
         }
     }
 
     pub fn set_modified_from_value(&mut self, value: &Value) {
         self.modified = match (&mut self.node, value) {
+            (Node::Unit(_), Value::Unit | Value::UnitStruct(_)) => true,
+            // `()` is parsed as an empty tuple
+            (Node::Unit(_), Value::Tuple(values)) if values.is_empty() => true,
+            (Node::Bool(node_bool), Value::Bool(_)) => true,
+            (Node::Char(_), Value::Char(_)) => true,
+            (Node::Number(node_number), Value::Number(_)) => true,
             (Node::String(node_string), Value::String(_)) => true,
             (Node::Array(node_array), Value::Array(values)) => {
                 if let Some(nodes) = &mut node_array.value {
@@ -105,6 +219,24 @@ impl NodeContainer {
 
                 true
             }
+            (Node::Map(node_map), Value::Map(map)) => {
+                if let Some(nodes) = &mut node_map.value {
+                    for (key, node) in nodes {
+                        if let Some(value) = map.0.get(key) {
+                            node.set_modified_from_value(value);
+                        }
+                    }
+                }
+
+                true
+            }
+            // `struct A {}` is written `()`, which is parsed as an empty tuple
+            (Node::Struct(node_struct), Value::Unit) if node_struct.fields.is_empty() => true,
+            (Node::Struct(node_struct), Value::Tuple(values))
+                if node_struct.fields.is_empty() && values.is_empty() =>
+            {
+                true
+            }
             (Node::Struct(node_struct), Value::Struct(_, map)) => {
                 for (name, field) in &mut node_struct.fields {
                     if let Some(value) = map.0.get(name) {
@@ -112,8 +244,47 @@ impl NodeContainer {
                     }
                 }
 
+                node_struct.unknown = map
+                    .iter()
+                    .filter(|(name, _)| !node_struct.fields.contains_key(*name))
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect();
+
+                true
+            }
+            (Node::Tuple(node_tuple), Value::Tuple(values) | Value::TupleStruct(_, values)) => {
+                node_tuple
+                    .fields
+                    .iter_mut()
+                    .zip(values.iter())
+                    .for_each(|(node, value)| {
+                        node.set_modified_from_value(value);
+                    });
+
+                true
+            }
+            (Node::Option(node_option), value)
+                if let Some(some_value) = value.as_implicit_option() =>
+            {
+                if let Some(some_value) = some_value
+                    && let Some(node) = node_option.some_node_mut()
+                {
+                    node.set_modified_from_value(some_value);
+                }
+
                 true
             }
+            (Node::Enum(node_enum), value) if value.enum_variant_name().is_some() => {
+                if let Some(node) = node_enum.active_node_mut() {
+                    node.set_modified_from_value(value);
+                }
+
+                true
+            }
+            (Node::Lazy(node_lazy), value) => {
+                node_lazy.modified_value = value.clone();
+                value.is_not_empty()
+            }
             _ => false,
         };
     }
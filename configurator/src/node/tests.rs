@@ -24,7 +24,7 @@ fn test_schema<S: RustSchemaTrait + Serialize>(config1: &S, is_default_complete:
     let ron = ron::to_string(&config1).unwrap();
 
     let ron_value = ron_value::from_str(&ron).unwrap();
-    let value = crate::providers::cosmic_ron::ron_value_to_value(ron_value);
+    let value = crate::providers::cosmic_ron::ron_value_to_value(ron_value).unwrap();
 
     dbg!(&value);
 
@@ -12,6 +12,13 @@ impl NodeContainer {
         }
 
         match &self.node {
+            Node::Unit(node_unit) => match &node_unit.name {
+                Some(name) => Some(Value::UnitStruct(name.clone())),
+                None => Some(Value::Unit),
+            },
+            Node::Bool(node_bool) => node_bool.value.map(Value::Bool),
+            Node::Char(node_char) => node_char.value.map(Value::Char),
+            Node::Number(node_number) => node_number.value.map(Value::Number),
             Node::String(node_string) => node_string
                 .value
                 .as_ref()
@@ -24,6 +31,9 @@ impl NodeContainer {
                         map.0.insert(key.to_owned(), value);
                     }
                 }
+
+                map.0.extend(node_struct.unknown.clone());
+
                 Some(Value::Struct(self.name.clone(), map))
             }
             Node::Array(node_array) => {
@@ -39,6 +49,50 @@ impl NodeContainer {
 
                 Some(Value::Array(values))
             }
+            Node::Map(node_map) => {
+                let mut map = Map::new();
+
+                if let Some(entries) = &node_map.value {
+                    map.0.reserve(entries.len());
+
+                    for (key, node) in entries {
+                        map.0.insert(key.clone(), node.to_value()?);
+                    }
+                }
+
+                Some(Value::Map(map))
+            }
+            Node::Tuple(node_tuple) => {
+                let mut values = Vec::with_capacity(node_tuple.fields.len());
+
+                for field in &node_tuple.fields {
+                    values.push(field.to_value()?);
+                }
+
+                match &node_tuple.name {
+                    Some(name) => Some(Value::TupleStruct(name.clone(), values)),
+                    None => Some(Value::Tuple(values)),
+                }
+            }
+            Node::Enum(node_enum) => {
+                let variant = &node_enum.variants[node_enum.value?];
+
+                match &variant.node {
+                    Some(node) => node.to_value(),
+                    None => Some(Value::UnitStruct(variant.name.clone())),
+                }
+            }
+            Node::Lazy(node_lazy) => node_lazy
+                .modified_value
+                .is_not_empty()
+                .then(|| node_lazy.modified_value.clone()),
+            Node::Option(node_option) => match node_option.is_some? {
+                true => {
+                    let value = node_option.some_node()?.to_value()?;
+                    Some(Value::Option(Some(Box::new(value))))
+                }
+                false => Some(Value::Option(None)),
+            },
         }
     }
 }
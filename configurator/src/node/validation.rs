@@ -0,0 +1,280 @@
+use std::fmt::Display;
+
+use rust_schema2::RustSchemaRoot;
+
+use crate::node::{Node, NodeContainer, data_path::DataPathType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValidationIssueKind {
+    MissingValue,
+    ArrayBelowMin,
+    ArrayAboveMax,
+    /// The input is not a number, or is out of range for its kind
+    InvalidNumber,
+    /// The input is not a single character
+    InvalidChar,
+    /// The active variant of an enum has issues in its payload
+    BadEnumPayload,
+}
+
+/// Something that prevents the tree from being written
+#[derive(Debug, Clone)]
+pub struct ValidationIssue {
+    /// Relative to the node which was validated
+    pub data_path: Vec<DataPathType>,
+    pub kind: ValidationIssueKind,
+    pub message: String,
+}
+
+impl Display for ValidationIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if !self.data_path.is_empty() {
+            let path = self
+                .data_path
+                .iter()
+                .map(|data| data.to_string())
+                .collect::<Vec<_>>()
+                .join("/");
+
+            write!(f, "{}: ", path)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl ValidationIssue {
+    fn new(data_path: &[DataPathType], kind: ValidationIssueKind, message: String) -> Self {
+        Self {
+            data_path: data_path.to_vec(),
+            kind,
+            message,
+        }
+    }
+
+    /// Return true if the issue is at `data_path` or in one of its children
+    pub fn is_under(&self, data_path: &[DataPathType]) -> bool {
+        self.data_path.starts_with(data_path)
+    }
+}
+
+impl NodeContainer {
+    /// Return true if all active nodes have a valid value
+    pub fn is_valid(&self, root: &RustSchemaRoot) -> bool {
+        self.validate(root).is_empty()
+    }
+
+    /// Collect the issues of all active nodes.
+    ///
+    /// `self` must be the root of the tree: lazy nodes are built with
+    /// the schema at their path, to validate the value they would write.
+    pub fn validate(&self, root: &RustSchemaRoot) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+        self.validate_rec(root, &mut Vec::new(), &mut issues);
+        issues
+    }
+
+    fn validate_rec(
+        &self,
+        root: &RustSchemaRoot,
+        data_path: &mut Vec<DataPathType>,
+        issues: &mut Vec<ValidationIssue>,
+    ) {
+        let missing_value = |data_path: &[DataPathType]| {
+            ValidationIssue::new(
+                data_path,
+                ValidationIssueKind::MissingValue,
+                "no value defined".to_string(),
+            )
+        };
+
+        let child = |data_path: &mut Vec<DataPathType>,
+                     data: DataPathType,
+                     node: &NodeContainer,
+                     issues: &mut Vec<ValidationIssue>| {
+            data_path.push(data);
+            node.validate_rec(root, data_path, issues);
+            data_path.pop();
+        };
+
+        match &self.node {
+            Node::Unit(_) => {}
+            Node::Bool(node_bool) => {
+                if node_bool.value.is_none() {
+                    issues.push(missing_value(data_path));
+                }
+            }
+            Node::Char(node_char) => {
+                if !node_char.value_string.is_empty()
+                    && let Err(e) = node_char.try_parse_from_str(&node_char.value_string)
+                {
+                    issues.push(ValidationIssue::new(
+                        data_path,
+                        ValidationIssueKind::InvalidChar,
+                        e.to_string(),
+                    ));
+                } else if node_char.value.is_none() {
+                    issues.push(missing_value(data_path));
+                }
+            }
+            Node::Number(node_number) => {
+                if !node_number.value_string.is_empty()
+                    && let Err(e) = node_number.try_parse_from_str(&node_number.value_string)
+                {
+                    issues.push(ValidationIssue::new(
+                        data_path,
+                        ValidationIssueKind::InvalidNumber,
+                        e.to_string(),
+                    ));
+                } else if node_number.value.is_none() {
+                    issues.push(missing_value(data_path));
+                }
+            }
+            Node::String(node_string) => {
+                if node_string.value.is_none() {
+                    issues.push(missing_value(data_path));
+                }
+            }
+            Node::Struct(node_struct) => {
+                for (name, field) in &node_struct.fields {
+                    child(data_path, DataPathType::Name(name.clone()), field, issues);
+                }
+            }
+            Node::Array(node_array) => match &node_array.value {
+                Some(values) => {
+                    if let Some(min) = node_array.min
+                        && values.len() < min as usize
+                    {
+                        issues.push(ValidationIssue::new(
+                            data_path,
+                            ValidationIssueKind::ArrayBelowMin,
+                            format!("{} values, expected at least {}", values.len(), min),
+                        ));
+                    }
+
+                    if let Some(max) = node_array.max
+                        && values.len() > max as usize
+                    {
+                        issues.push(ValidationIssue::new(
+                            data_path,
+                            ValidationIssueKind::ArrayAboveMax,
+                            format!("{} values, expected at most {}", values.len(), max),
+                        ));
+                    }
+
+                    for (pos, node) in values.iter().enumerate() {
+                        child(data_path, DataPathType::Indice(pos), node, issues);
+                    }
+                }
+                None => issues.push(missing_value(data_path)),
+            },
+            Node::Map(node_map) => match &node_map.value {
+                Some(entries) => {
+                    for (key, node) in entries {
+                        child(data_path, DataPathType::Name(key.clone()), node, issues);
+                    }
+                }
+                None => issues.push(missing_value(data_path)),
+            },
+            Node::Tuple(node_tuple) => {
+                for (pos, field) in node_tuple.fields.iter().enumerate() {
+                    child(data_path, DataPathType::Indice(pos), field, issues);
+                }
+            }
+            Node::Enum(node_enum) => match node_enum.value {
+                Some(pos) => {
+                    if let Some(node) = node_enum.active_node() {
+                        let mut payload_issues = Vec::new();
+                        child(
+                            data_path,
+                            DataPathType::Indice(pos),
+                            node,
+                            &mut payload_issues,
+                        );
+
+                        if !payload_issues.is_empty() {
+                            issues.push(ValidationIssue::new(
+                                data_path,
+                                ValidationIssueKind::BadEnumPayload,
+                                format!(
+                                    "the payload of {} is not valid",
+                                    node_enum.variants[pos].name
+                                ),
+                            ));
+                            issues.extend(payload_issues);
+                        }
+                    }
+                }
+                None => issues.push(missing_value(data_path)),
+            },
+            Node::Option(node_option) => match node_option.is_some {
+                Some(true) => match node_option.some_node() {
+                    Some(node) => child(data_path, DataPathType::Indice(0), node, issues),
+                    None => issues.push(missing_value(data_path)),
+                },
+                Some(false) => {}
+                None => issues.push(missing_value(data_path)),
+            },
+            // built on a copy, so the tree stays lazy
+            Node::Lazy(_) => {
+                let mut built = self.clone();
+                built.build_if_lazy(root, data_path);
+
+                if !matches!(built.node, Node::Lazy(_)) {
+                    built.validate_rec(root, data_path, issues);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{generic_value::Value, node::NodeChar};
+    use rust_schema2::schema_for;
+
+    fn char_container(value: char) -> NodeContainer {
+        NodeContainer::from_node(Node::Char(NodeChar::new(Some(value)))).set_is_modified(true)
+    }
+
+    #[test]
+    fn clear_char_input() {
+        let mut node = char_container('a');
+
+        assert!(
+            node.node
+                .unwrap_char_mut()
+                .set_from_str(String::new())
+                .is_err()
+        );
+
+        assert_eq!(node.to_value(), None);
+        let issues = node.validate(&schema_for::<char>());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, ValidationIssueKind::MissingValue);
+    }
+
+    #[test]
+    fn invalid_char_input() {
+        let mut node = char_container('a');
+
+        assert!(
+            node.node
+                .unwrap_char_mut()
+                .set_from_str("ab".to_string())
+                .is_err()
+        );
+
+        assert_eq!(node.to_value(), None);
+        let issues = node.validate(&schema_for::<char>());
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].kind, ValidationIssueKind::InvalidChar);
+
+        node.node
+            .unwrap_char_mut()
+            .set_from_str("b".to_string())
+            .unwrap();
+        assert_eq!(node.to_value(), Some(Value::Char('b')));
+        assert!(node.validate(&schema_for::<char>()).is_empty());
+    }
+}
@@ -1,10 +1,11 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::{self, File},
     io::Read,
     iter::{self},
     path::{Path, PathBuf},
     str::FromStr,
+    sync::Arc,
 };
 
 use anyhow::{anyhow, bail};
@@ -17,18 +18,18 @@ use rust_schema2::RustSchemaRoot;
 use crate::{
     app::{self, Dialog},
     config::Config,
-    generic_value::Value,
+    generic_value::{KeyDiff, Value},
+    history::History,
     message::{ChangeMsg, PageMsg},
     node::{
-        self, Node, NodeContainer,
-        data_path::{DataPath, DataPathType},
+        self, Node, NodeContainer, ValidationIssue,
+        data_path::{self, DataPath, DataPathType},
         schema_at,
     },
-    providers,
+    providers::{Fingerprint, Provider, ReadError, Registry},
+    watcher::WatchedPath,
 };
 
-use configurator_utils::ConfigFormat;
-
 #[derive(Debug)]
 pub struct Page {
     pub appid: String,
@@ -37,7 +38,7 @@ pub struct Page {
     pub source_paths: Vec<PathBuf>,
     pub source_home_path: PathBuf,
     pub write_path: PathBuf,
-    pub format: ConfigFormat,
+    pub provider: Arc<dyn Provider>,
 
     pub system_config: Value,
     pub user_config: Value,
@@ -46,12 +47,46 @@ pub struct Page {
     pub schema_root: RustSchemaRoot,
     pub tree: NodeContainer,
     pub data_path: DataPath,
+    /// Issues that prevent the tree from being written
+    pub issues: Vec<ValidationIssue>,
+    pub history: History,
+    /// Files that can't be read. The page shows them instead of the tree,
+    /// until they are ignored or reset.
+    pub read_errors: Vec<ReadError>,
+    /// Files whose read errors were ignored, so they are not shown again
+    pub ignored_read_errors: HashSet<PathBuf>,
+    /// Files of the user config when it was last read
+    pub fingerprint: Fingerprint,
+    /// The tree has changes which are not written, because of its issues or of a conflict
+    pub unwritten: bool,
+    /// The user config changed on disk since it was read, and the tree must be written.
+    /// Nothing is written until it is resolved.
+    pub conflict: Option<Conflict>,
+}
+
+/// Changes of the tree, and of someone else on disk, to the same user config
+#[derive(Debug)]
+pub struct Conflict {
+    /// The user config on disk
+    pub theirs: Value,
+    /// Keys changed both in the tree and on disk, to different values
+    pub diffs: Vec<KeyDiff>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Resolution {
+    /// Keep the changes of both, and the ones of the tree for the keys changed by both
+    Merge,
+    /// Write the tree over the config on disk
+    KeepMine,
+    /// Reload the config on disk, losing the changes of the tree
+    TakeTheirs,
 }
 
 impl Page {
     // need &str for appid: https://github.com/tokio-rs/tracing/issues/1181
-    #[instrument(skip(content))]
-    fn from_str(appid: &str, content: &str) -> anyhow::Result<Self> {
+    #[instrument(skip(content, registry))]
+    fn from_str(appid: &str, content: &str, registry: &Registry) -> anyhow::Result<Self> {
         let json_value = json::Value::from_str(content)?;
 
         let Some(json_obj) = json_value.as_object() else {
@@ -88,25 +123,50 @@ impl Page {
         let format = {
             if let Some(json::Value::String(format)) = json_obj.get("X_CONFIGURATOR_FORMAT") {
                 format
+            } else if let Some(extension) = source_home_path
+                .extension()
+                .and_then(|extension| extension.to_str())
+            {
+                extension
             } else {
-                source_home_path
-                    .extension()
-                    .expect("no format defined")
-                    .to_str()
-                    .unwrap()
+                bail!("no X_CONFIGURATOR_FORMAT, and no extension on the source home path")
             }
         };
 
-        let format = ConfigFormat::try_from(format)?;
+        let Some(provider) = registry.get(format) else {
+            bail!(
+                "unknown format {format:?}, expected one of: {}",
+                registry.names().collect::<Vec<_>>().join(", ")
+            )
+        };
+
+        let schema_root: RustSchemaRoot = json::from_value(json_value)?;
+
+        // taken before reading, so a change made while we read is seen as a conflict
+        let fingerprint = Fingerprint::read(
+            [source_home_path.as_path(), write_path.as_path()],
+            provider.layout(),
+        );
+
+        let mut read_errors = Vec::new();
 
         let mut system_config = Value::Empty;
 
         for path in &source_paths {
-            system_config = system_config.merge(&providers::read_from_format(path, &format))
+            system_config = system_config.merge(&read_or_record(
+                &*provider,
+                path,
+                &schema_root,
+                &mut read_errors,
+            ))
         }
 
-        let user_config =
-            Value::Empty.merge(&providers::read_from_format(&source_home_path, &format));
+        let user_config = Value::Empty.merge(&read_or_record(
+            &*provider,
+            &source_home_path,
+            &schema_root,
+            &mut read_errors,
+        ));
 
         let full_config = Value::Empty.merge(&system_config).merge(&user_config);
 
@@ -114,8 +174,6 @@ impl Page {
 
         let data_path = DataPath::new();
 
-        let schema_root = json::from_value(json_value)?;
-
         let mut tree = NodeContainer::from_schema_and_value(
             &schema_root,
             schema_root.resolve_schema(&schema_root.schema).unwrap(),
@@ -127,6 +185,8 @@ impl Page {
 
         dbg!(&tree);
 
+        let issues = tree.validate(&schema_root);
+
         let title = appid.split('.').next_back().unwrap().to_string();
 
         let page = Self {
@@ -139,9 +199,16 @@ impl Page {
             source_paths,
             source_home_path,
             write_path,
-            format,
+            provider,
             schema_root,
             tree,
+            issues,
+            history: History::default(),
+            read_errors,
+            ignored_read_errors: HashSet::new(),
+            fingerprint,
+            unwritten: false,
+            conflict: None,
         };
 
         Ok(page)
@@ -152,14 +219,34 @@ impl Page {
     }
 
     #[instrument(skip_all)]
-    pub fn reload_config(&mut self) -> anyhow::Result<()> {
+    pub fn reload_config(&mut self) {
         info!("reload the config");
 
-        self.user_config = Value::Empty.merge(&providers::read_from_format(
+        self.read_errors.clear();
+
+        self.fingerprint = Fingerprint::read(self.user_paths(), self.provider.layout());
+
+        self.system_config = Value::Empty;
+
+        for path in &self.source_paths {
+            self.system_config = self.system_config.merge(&read_or_record(
+                &*self.provider,
+                path,
+                &self.schema_root,
+                &mut self.read_errors,
+            ));
+        }
+
+        self.user_config = Value::Empty.merge(&read_or_record(
+            &*self.provider,
             &self.source_home_path,
-            &self.format,
+            &self.schema_root,
+            &mut self.read_errors,
         ));
 
+        self.read_errors
+            .retain(|e| !self.ignored_read_errors.contains(&e.path));
+
         debug!("user_config = {:#?}", self.user_config);
 
         debug!("system_config = {:#?}", self.system_config);
@@ -169,13 +256,83 @@ impl Page {
             .merge(&self.user_config);
 
         debug!("full_config = {:#?}", self.full_config);
-
-        Ok(())
     }
 
     #[instrument(skip_all)]
     pub fn reload_page(&mut self) -> anyhow::Result<()> {
-        self.reload_config()?;
+        let previous_user_config = self.user_config.clone();
+
+        self.reload_config();
+
+        // the history can't be applied on a tree changed by someone else
+        if self.user_config != previous_user_config {
+            info!("the config was changed externally, clear the history");
+            self.history.clear();
+        }
+
+        self.rebuild_tree();
+
+        Ok(())
+    }
+
+    /// Paths of the config, to reload the page when they change
+    pub fn watched_paths(&self) -> Vec<WatchedPath> {
+        let layout = self.provider.layout();
+
+        let mut paths: Vec<WatchedPath> = self
+            .source_paths
+            .iter()
+            .chain([&self.source_home_path, &self.write_path])
+            .map(|path| WatchedPath {
+                path: path.clone(),
+                layout,
+            })
+            .collect();
+
+        paths.dedup();
+        paths
+    }
+
+    /// Called when a file under [`Page::watched_paths`] changed.
+    ///
+    /// The files we write are also reported, but the config we read
+    /// is then the one we already have, so nothing is done.
+    pub fn on_config_changed(&mut self) {
+        // a reload would lose the changes of the tree,
+        // so they are checked against the files when written
+        if self.unwritten {
+            info!("the config was changed externally, but the page has changes not written");
+
+            if self.conflict.is_some() {
+                self.check_conflict();
+            }
+
+            return;
+        }
+
+        let previous_system_config = self.system_config.clone();
+        let previous_user_config = self.user_config.clone();
+
+        self.reload_config();
+
+        if self.system_config == previous_system_config && self.user_config == previous_user_config
+        {
+            return;
+        }
+
+        info!("the config was changed externally, reload the page");
+
+        if self.user_config != previous_user_config {
+            self.history.clear();
+        }
+
+        self.rebuild_tree();
+    }
+
+    /// Build the tree from the config, keeping the current path
+    /// and the inputs not written yet, when their nodes still exist
+    fn rebuild_tree(&mut self) {
+        let pending_inputs = self.tree.pending_inputs();
 
         self.tree = NodeContainer::from_schema_and_value(
             &self.schema_root,
@@ -188,15 +345,196 @@ impl Page {
 
         self.tree.set_modified_from_value(&self.user_config);
 
+        self.tree.build_lazy(&self.schema_root, &self.data_path.vec);
         self.data_path.sanitize_path(&self.tree);
 
+        self.tree.restore_pending_inputs(pending_inputs);
+
+        self.issues = self.tree.validate(&self.schema_root);
+
+        // the tree is now the config on disk
+        self.unwritten = false;
+        self.conflict = None;
+    }
+
+    /// Paths of the user config, which we write
+    fn user_paths(&self) -> [&Path; 2] {
+        [&self.source_home_path, &self.write_path]
+    }
+
+    /// Return `true` if the user config changed on disk since it was read.
+    /// In this case, the tree must not be written: the changes are merged
+    /// with the files when they don't overlap, otherwise [`Page::conflict`] is set.
+    fn check_conflict(&mut self) -> bool {
+        if !self
+            .fingerprint
+            .is_outdated(self.user_paths(), self.provider.layout())
+        {
+            self.conflict = None;
+            return false;
+        }
+
+        let mut read_errors = Vec::new();
+
+        let theirs = Value::Empty.merge(&read_or_record(
+            &*self.provider,
+            &self.source_home_path,
+            &self.schema_root,
+            &mut read_errors,
+        ));
+
+        read_errors.retain(|e| !self.ignored_read_errors.contains(&e.path));
+
+        if !read_errors.is_empty() {
+            self.read_errors.extend(read_errors);
+            self.unwritten = true;
+            return true;
+        }
+
+        let mine = self.tree.to_value().unwrap_or(Value::Empty);
+
+        let diffs = Value::diff3(&self.user_config, &mine, &theirs);
+
+        if diffs.is_empty() {
+            // the changes don't overlap, so we keep both
+            self.conflict = None;
+
+            let merged = Value::merge3(&self.user_config, &mine, &theirs);
+
+            if let Err(e) = self.write_value(&theirs, merged.is_not_empty().then_some(merged)) {
+                error!("can't write the config: {e}");
+                self.unwritten = true;
+                return true;
+            }
+
+            self.unwritten = false;
+
+            if let Err(e) = self.reload_page() {
+                error!("can't reload the page: {e}");
+            }
+        } else {
+            info!(
+                "the config was changed externally: {} conflicting keys",
+                diffs.len()
+            );
+            self.conflict = Some(Conflict { theirs, diffs });
+            self.unwritten = true;
+        }
+
+        true
+    }
+
+    fn resolve_conflict(&mut self, resolution: Resolution) -> anyhow::Result<()> {
+        let Some(conflict) = self.conflict.take() else {
+            return Ok(());
+        };
+
+        let mine = self.tree.to_value();
+
+        match resolution {
+            Resolution::Merge => {
+                let merged = Value::merge3(
+                    &self.user_config,
+                    mine.as_ref().unwrap_or(&Value::Empty),
+                    &conflict.theirs,
+                );
+                self.write_value(&conflict.theirs, merged.is_not_empty().then_some(merged))?;
+                self.reload_page()?;
+            }
+            Resolution::KeepMine => {
+                self.write_value(&conflict.theirs, mine)?;
+                self.unwritten = false;
+                self.reload_config();
+            }
+            Resolution::TakeTheirs => {
+                self.reload_page()?;
+            }
+        }
+
         Ok(())
     }
 
-    pub fn write(&self) -> anyhow::Result<()> {
+    /// Write the tree only if it has no validation issue
+    fn validate_and_write(&mut self) {
+        self.issues = self.tree.validate(&self.schema_root);
+
+        if !self.issues.is_empty() {
+            info!("tree is not valid: {} issues", self.issues.len());
+            debug!("{:#?}", self.issues);
+            self.unwritten = true;
+            return;
+        }
+
+        if self.check_conflict() {
+            return;
+        }
+
         let value = self.tree.to_value();
+
+        if let Err(e) = self.write_value(&self.user_config, value.clone()) {
+            error!("can't write the config: {e}");
+            // retried on the next change
+            self.unwritten = true;
+            return;
+        }
+
+        self.unwritten = false;
+        // keep the user config in sync with what we wrote, to detect
+        // external changes, without reading all the files again
+        if self.write_path == self.source_home_path {
+            self.user_config = value.unwrap_or(Value::Empty);
+        }
+        self.fingerprint.update(
+            [self.source_home_path.as_path(), self.write_path.as_path()],
+            self.provider.layout(),
+        );
+    }
+
+    /// The restored subtree can have lazy nodes, or not contain the current path anymore
+    fn after_history_change(&mut self) {
+        self.tree.build_lazy(&self.schema_root, &self.data_path.vec);
+        self.data_path.sanitize_path(&self.tree);
+
+        self.validate_and_write();
+    }
+
+    pub fn write(&self) -> anyhow::Result<()> {
+        self.write_value(&self.user_config, self.tree.to_value())
+    }
+
+    /// `previous` is the user config on disk, read from [`Page::source_home_path`]
+    fn write_value(&self, previous: &Value, value: Option<Value>) -> anyhow::Result<()> {
         debug!("write value: {:?}", value);
-        providers::write(&self.write_path, &self.format, value)?;
+
+        // the provider only writes what changed since `previous`,
+        // which must be the config at the path we write
+        let written;
+        let previous = if self.write_path == self.source_home_path {
+            previous
+        } else {
+            written = self
+                .provider
+                .read(&self.write_path, &self.schema_root)
+                .unwrap_or_else(|e| {
+                    warn!("{e}");
+                    Value::Empty
+                });
+            &written
+        };
+
+        let no_removed_unknown = HashSet::new();
+        let removed_unknown = match &self.tree.node {
+            Node::Struct(node_struct) => &node_struct.removed_unknown,
+            _ => &no_removed_unknown,
+        };
+
+        self.provider.write(
+            &self.write_path,
+            &self.schema_root,
+            previous,
+            value,
+            removed_unknown,
+        )?;
 
         Ok(())
     }
@@ -211,17 +549,23 @@ pub enum Action {
 
 impl Page {
     pub fn update(&mut self, message: PageMsg, page_id: Entity) -> Action {
-        let action = Action::None;
+        let mut action = Action::None;
 
         match message {
             PageMsg::SelectDataPath(pos) => {
                 self.data_path.change_to(pos);
+                self.tree
+                    .build_lazy(&self.schema_root, self.data_path.current());
             }
             PageMsg::OpenDataPath(data_path_type) => {
                 self.data_path.open(data_path_type);
+                self.tree
+                    .build_lazy(&self.schema_root, self.data_path.current());
             }
 
             PageMsg::ApplyDefault(data_path) => {
+                let snapshot = History::snapshot(&self.tree, &data_path);
+
                 // we can this before, to avoid setting modified
                 // to the node we just created
                 self.tree.set_modified2(data_path.iter());
@@ -243,34 +587,113 @@ impl Page {
                 .set_is_removable(node.is_removable);
 
                 let is_modified = match &new_node.node {
+                    Node::Unit(_) => false,
+                    Node::Bool(_) => false,
+                    Node::Number(_) => false,
+                    Node::Char(_) => false,
                     Node::String(_) => false,
                     Node::Array(_) => false,
+                    Node::Map(_) => false,
                     Node::Struct(_) => true,
+                    Node::Tuple(_) => true,
+                    Node::Enum(_) => false,
+                    Node::Option(_) => false,
+                    Node::Lazy(_) => false,
                 };
 
                 *node = new_node.set_is_modified(is_modified);
 
+                self.history.push(snapshot);
+
+                self.tree.build_lazy(&self.schema_root, &self.data_path.vec);
                 self.data_path.sanitize_path(&self.tree);
 
-                if self.tree.is_valid() {
-                    self.write().unwrap();
-                } else {
-                    info!("tree is not valid")
-                }
+                self.validate_and_write();
             }
             PageMsg::ChangeMsg(data_path, change_msg) => {
                 debug!("{:?} {:?}", data_path, change_msg);
 
+                // only pushed if the change is applied
+                let snapshot = History::snapshot(&self.tree, &data_path);
+
                 let node = self.tree.get_at_mut(Box::new(data_path.iter())).unwrap();
 
                 match change_msg {
-                    ChangeMsg::ChangeBool(_) => todo!(),
+                    ChangeMsg::ChangeBool(value) => {
+                        let node_bool = node.node.unwrap_bool_mut();
+                        node_bool.value = Some(value);
+                    }
                     ChangeMsg::ChangeString(value) => {
                         let node_string = node.node.unwrap_string_mut();
                         node_string.value = Some(value);
                     }
-                    ChangeMsg::ChangeNumber(_) => todo!(),
-                    ChangeMsg::ChangeEnum(_) => todo!(),
+                    ChangeMsg::ChangeNumber(value) => {
+                        let node_number = node.node.unwrap_number_mut();
+
+                        if let Err(e) = node_number.set_from_str(value) {
+                            // keep the text so the view can show the error,
+                            // but don't write an invalid number
+                            debug!("{e}");
+                            self.issues = self.tree.validate(&self.schema_root);
+                            self.unwritten = true;
+                            return Action::None;
+                        }
+                    }
+                    ChangeMsg::ChangeChar(value) => {
+                        let node_char = node.node.unwrap_char_mut();
+
+                        if let Err(e) = node_char.set_from_str(value) {
+                            debug!("{e}");
+                            self.issues = self.tree.validate(&self.schema_root);
+                            self.unwritten = true;
+                            return Action::None;
+                        }
+                    }
+                    ChangeMsg::ChangeEnum(pos) => {
+                        let enum_schema = schema_at(&self.schema_root, &data_path).unwrap();
+                        let enum_ = enum_schema.as_enum().unwrap();
+
+                        let node_enum = node.node.unwrap_enum_mut();
+                        let variant = &mut node_enum.variants[pos];
+
+                        // build the payload the first time this variant is selected
+                        if variant.node.is_none() {
+                            variant.node = NodeContainer::from_enum_variant(
+                                &self.schema_root,
+                                &enum_.variants[pos],
+                                &Value::Empty,
+                                &node.default,
+                            );
+                        }
+
+                        node_enum.value = Some(pos);
+                    }
+                    ChangeMsg::ChangeOption(is_some) => {
+                        let node_option = node.node.unwrap_option_mut();
+
+                        // build the inner node the first time Some is selected
+                        if is_some && node_option.node.is_none() {
+                            let schema =
+                                schema_at(&self.schema_root, &data_path::push_one(&data_path, 0))
+                                    .unwrap();
+
+                            let default = node
+                                .default
+                                .as_implicit_option()
+                                .flatten()
+                                .unwrap_or(&Value::Empty);
+
+                            node_option.node =
+                                Some(Box::new(NodeContainer::from_schema_and_value(
+                                    &self.schema_root,
+                                    schema,
+                                    default,
+                                    default,
+                                )));
+                        }
+
+                        node_option.is_some = Some(is_some);
+                    }
                     ChangeMsg::Remove(data) => match &mut node.node {
                         Node::Array(node_array) => {
                             node_array
@@ -286,9 +709,43 @@ impl Page {
 
                             node.modified = true;
                         }
+                        Node::Map(node_map) => {
+                            node_map.remove(data.unwrap_name_ref());
+                            node.modified = true;
+                        }
                         _ => panic!(),
                     },
-                    ChangeMsg::AddNewNodeToObject(_) => todo!(),
+                    ChangeMsg::RemoveUnknown(key) => {
+                        let node_struct = node.node.unwrap_struct_mut();
+                        if node_struct.unknown.shift_remove(&key).is_some() {
+                            node_struct.removed_unknown.insert(key);
+                        }
+                    }
+                    ChangeMsg::AddNewNodeToObject(key) => {
+                        let schema = schema_at(
+                            &self.schema_root,
+                            &data_path::push_one(&data_path, key.as_str()),
+                        )
+                        .unwrap();
+
+                        let new_node = NodeContainer::from_schema_and_value(
+                            &self.schema_root,
+                            schema,
+                            &Value::Empty,
+                            &Value::Empty,
+                        )
+                        .set_is_removable(true);
+
+                        let node_map = node.node.unwrap_map_mut();
+
+                        if !node_map.insert(key, new_node) {
+                            // keep the dialog open so the key can be changed
+                            debug!("key already exists");
+                            return Action::None;
+                        }
+
+                        action = Action::RemoveDialog;
+                    }
 
                     ChangeMsg::AddNewNodeToArray => {
                         let node_array = node.node.unwrap_array_mut();
@@ -321,17 +778,75 @@ impl Page {
                             }
                         }
                     }
-                    ChangeMsg::RenameKey { prev, new } => todo!(),
+                    ChangeMsg::RenameKey { prev, new } => {
+                        if prev != new {
+                            let node_map = node.node.unwrap_map_mut();
+
+                            if !node_map.rename(&prev, new) {
+                                debug!("key already exists");
+                                return Action::None;
+                            }
+                        }
+
+                        action = Action::RemoveDialog;
+                    }
                 }
 
                 self.tree.set_modified2(data_path.iter());
 
+                self.history.push(snapshot);
+
                 self.data_path.sanitize_path(&self.tree);
 
-                if self.tree.is_valid() {
-                    self.write().unwrap();
-                } else {
-                    info!("tree is not valid")
+                self.validate_and_write();
+            }
+            PageMsg::Undo => {
+                if self.history.undo(&mut self.tree) {
+                    self.after_history_change();
+                }
+            }
+            PageMsg::Redo => {
+                if self.history.redo(&mut self.tree) {
+                    self.after_history_change();
+                }
+            }
+            PageMsg::IgnoreReadError(pos) => {
+                let read_error = self.read_errors.remove(pos);
+                info!("ignore {}", read_error.path.display());
+                self.ignored_read_errors.insert(read_error.path);
+            }
+            PageMsg::ResetReadError(pos) => {
+                let read_error = self.read_errors.remove(pos);
+
+                // keep a backup instead of losing the file.
+                // Dotfiles are not read.
+                let file_name = read_error
+                    .path
+                    .file_name()
+                    .unwrap_or_default()
+                    .to_string_lossy();
+                let backup_path = read_error.path.with_file_name(format!(".{file_name}.bak"));
+
+                match fs::rename(&read_error.path, &backup_path) {
+                    Ok(()) => {
+                        info!(
+                            "moved {} to {}",
+                            read_error.path.display(),
+                            backup_path.display()
+                        );
+                        if let Err(e) = self.reload_page() {
+                            error!("{e}");
+                        }
+                    }
+                    Err(e) => {
+                        error!("can't move {}: {e}", read_error.path.display());
+                        self.read_errors.insert(pos, read_error);
+                    }
+                }
+            }
+            PageMsg::ResolveConflict(resolution) => {
+                if let Err(e) = self.resolve_conflict(resolution) {
+                    error!("{e}");
                 }
             }
 
@@ -469,7 +984,7 @@ impl Page {
 
                 self.data_path.sanitize_path(&self.tree);
 
-                if self.tree.is_valid() {
+                if self.tree.is_valid(&self.schema_root) {
                     self.write().unwrap();
                 }
             }
@@ -498,7 +1013,27 @@ impl Page {
     }
 }
 
-pub fn create_pages(config: &Config) -> impl Iterator<Item = Page> + use<'_> {
+/// Read the config at `path`, recording the error instead of failing
+fn read_or_record(
+    provider: &dyn Provider,
+    path: &Path,
+    root: &RustSchemaRoot,
+    read_errors: &mut Vec<ReadError>,
+) -> Value {
+    match provider.read(path, root) {
+        Ok(value) => value,
+        Err(e) => {
+            error!("{e}");
+            read_errors.push(e);
+            Value::Empty
+        }
+    }
+}
+
+pub fn create_pages<'a>(
+    config: &'a Config,
+    registry: &'a Registry,
+) -> impl Iterator<Item = Page> + use<'a> {
     #[allow(clippy::vec_init_then_push)]
     fn default_paths() -> impl Iterator<Item = PathBuf> {
         let mut data_dirs: Vec<PathBuf> = vec![];
@@ -515,7 +1050,10 @@ pub fn create_pages(config: &Config) -> impl Iterator<Item = Page> + use<'_> {
         data_dirs.into_iter().map(|d| d.join("configurator"))
     }
 
-    fn cosmic_compat(config: &Config) -> Box<dyn Iterator<Item = Page> + '_> {
+    fn cosmic_compat<'a>(
+        config: &'a Config,
+        registry: &'a Registry,
+    ) -> Box<dyn Iterator<Item = Page> + 'a> {
         if config.cosmic_compat {
             let dir = include_dir!("$CARGO_MANIFEST_DIR/../cosmic_compat/schemas");
 
@@ -526,10 +1064,16 @@ pub fn create_pages(config: &Config) -> impl Iterator<Item = Page> + use<'_> {
 
                 let appid = appid_from_schema_path(file.path())?;
 
-                if !config.masked.contains(&appid) {
-                    Some(Page::from_str(&appid, content).unwrap())
-                } else {
-                    None
+                if config.masked.contains(&appid) {
+                    return None;
+                }
+
+                match Page::from_str(&appid, content, registry) {
+                    Ok(page) => Some(page),
+                    Err(e) => {
+                        error!("{}", e);
+                        None
+                    }
                 }
             }))
         } else {
@@ -563,7 +1107,7 @@ pub fn create_pages(config: &Config) -> impl Iterator<Item = Page> + use<'_> {
 
             if !config.masked.contains(&appid) {
                 match fs::read_to_string(&schema_path) {
-                    Ok(content) => match Page::from_str(&appid, &content) {
+                    Ok(content) => match Page::from_str(&appid, &content, registry) {
                         Ok(page) => Some(page),
                         Err(e) => {
                             error!("{}", e);
@@ -579,7 +1123,7 @@ pub fn create_pages(config: &Config) -> impl Iterator<Item = Page> + use<'_> {
                 None
             }
         })
-        .chain(cosmic_compat(config))
+        .chain(cosmic_compat(config, registry))
 }
 
 fn appid_from_schema_path(schema_path: &Path) -> Option<String> {
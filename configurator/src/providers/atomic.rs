@@ -0,0 +1,190 @@
+//! Crash-safe writes: the content goes to a temporary file in the same directory,
+//! is synced to the disk, and is renamed over the target. A rename is atomic,
+//! so readers see either the old file or the new one, never a truncated one.
+
+use std::{
+    fs::{self, File},
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::anyhow;
+
+/// Write `contents` at `path` atomically, creating the parent directories.
+pub fn write(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+    let tmp_path = stage(path, contents)?;
+
+    if let Err(e) = fs::rename(&tmp_path, path) {
+        let _ = fs::remove_file(&tmp_path);
+        return Err(e.into());
+    }
+
+    sync_parent(path)
+}
+
+/// Remove the file at `path` if it exists, and make the removal durable.
+pub fn remove(path: &Path) -> anyhow::Result<()> {
+    match fs::remove_file(path) {
+        Ok(()) => sync_parent(path),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Write `contents` in a temporary file next to `path`, and return its path.
+fn stage(path: &Path, contents: &[u8]) -> anyhow::Result<PathBuf> {
+    let parent = path.parent().ok_or(anyhow!("no parent"))?;
+    let filename = path
+        .file_name()
+        .and_then(|filename| filename.to_str())
+        .ok_or(anyhow!("no filename"))?;
+
+    fs::create_dir_all(parent)?;
+
+    let tmp_path = parent.join(format!(".{filename}.{}.tmp", std::process::id()));
+
+    let res = (|| {
+        let mut file = File::create(&tmp_path)?;
+
+        // keep the permissions of the file we replace
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+
+        file.write_all(contents)?;
+        file.sync_all()
+    })();
+
+    match res {
+        Ok(()) => Ok(tmp_path),
+        Err(e) => {
+            let _ = fs::remove_file(&tmp_path);
+            Err(e.into())
+        }
+    }
+}
+
+/// Make the rename durable
+fn sync_parent(path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        File::open(parent)?.sync_all()?;
+    }
+
+    Ok(())
+}
+
+#[derive(Debug)]
+enum Operation {
+    Write { path: PathBuf, tmp_path: PathBuf },
+    Remove { path: PathBuf },
+}
+
+/// Changes to several files, applied all together or not at all.
+///
+/// Writes are staged in temporary files, so a failure while staging
+/// leaves the files untouched. If applying a change fails, the changes
+/// already applied are reverted from a backup of the previous content.
+#[derive(Debug, Default)]
+pub struct Transaction {
+    operations: Vec<Operation>,
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write(&mut self, path: &Path, contents: &[u8]) -> anyhow::Result<()> {
+        let tmp_path = stage(path, contents)?;
+
+        self.operations.push(Operation::Write {
+            path: path.to_path_buf(),
+            tmp_path,
+        });
+
+        Ok(())
+    }
+
+    pub fn remove(&mut self, path: &Path) {
+        self.operations.push(Operation::Remove {
+            path: path.to_path_buf(),
+        });
+    }
+
+    pub fn commit(mut self) -> anyhow::Result<()> {
+        // previous content of the files already changed
+        let mut applied = Vec::new();
+
+        while !self.operations.is_empty() {
+            // the operations left are cleaned up on drop
+            let operation = self.operations.remove(0);
+
+            match operation.apply() {
+                Ok(backup) => applied.push((operation.path().to_path_buf(), backup)),
+                Err(e) => {
+                    if let Operation::Write { tmp_path, .. } = operation {
+                        let _ = fs::remove_file(tmp_path);
+                    }
+                    rollback(applied);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        for (path, _) in &applied {
+            sync_parent(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Operation {
+    fn path(&self) -> &Path {
+        match self {
+            Operation::Write { path, .. } | Operation::Remove { path } => path,
+        }
+    }
+
+    /// Return the previous content of the file
+    fn apply(&self) -> std::io::Result<Option<Vec<u8>>> {
+        let backup = match fs::read(self.path()) {
+            Ok(contents) => Some(contents),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e),
+        };
+
+        match self {
+            Operation::Write { path, tmp_path } => fs::rename(tmp_path, path)?,
+            Operation::Remove { path } if backup.is_some() => fs::remove_file(path)?,
+            Operation::Remove { .. } => {}
+        }
+
+        Ok(backup)
+    }
+}
+
+fn rollback(applied: Vec<(PathBuf, Option<Vec<u8>>)>) {
+    for (path, backup) in applied.into_iter().rev() {
+        let res = match backup {
+            Some(contents) => write(&path, &contents),
+            None if path.exists() => fs::remove_file(&path).map_err(Into::into),
+            None => Ok(()),
+        };
+
+        if let Err(e) = res {
+            error!("can't restore {}: {e}", path.display());
+        }
+    }
+}
+
+impl Drop for Transaction {
+    /// Remove the staged files which were not committed
+    fn drop(&mut self) {
+        for operation in &self.operations {
+            if let Operation::Write { tmp_path, .. } = operation {
+                let _ = fs::remove_file(tmp_path);
+            }
+        }
+    }
+}
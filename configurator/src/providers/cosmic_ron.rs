@@ -1,16 +1,19 @@
 use std::{
+    collections::HashSet,
     fs,
     path::{Path, PathBuf},
 };
 
 use anyhow::{anyhow, bail};
-
+use indexmap::IndexMap;
+use ron_value::PrettyConfig;
+use rust_schema2::{RustSchemaKind, RustSchemaRoot, StructField};
 use serde::de::Error;
 
 use crate::{
     generic_value::{F32, F64, Map, Number, Value},
     node::{Node, NodeContainer},
-    providers::write_and_create_parent,
+    providers::{Layout, Position, Provider, ReadError, atomic::Transaction, ron_extensions},
 };
 
 // public until generic value implement serde
@@ -69,9 +72,9 @@ pub fn value_to_ron_value(value: Value) -> ron_value::Value {
 }
 
 // public until generic value implement serde
-pub fn ron_value_to_value(value: ron_value::Value) -> Value {
-    match value {
-        ron_value::Value::Unit => Value::Empty,
+pub fn ron_value_to_value(value: ron_value::Value) -> anyhow::Result<Value> {
+    let value = match value {
+        ron_value::Value::Unit => Value::Unit,
         ron_value::Value::Bool(bool) => Value::Bool(bool),
         ron_value::Value::Char(c) => Value::Char(c),
         ron_value::Value::Number(number) => Value::Number(match number {
@@ -90,65 +93,120 @@ pub fn ron_value_to_value(value: ron_value::Value) -> Value {
         }),
         ron_value::Value::String(s) => Value::String(s),
         ron_value::Value::Bytes(bytes) => Value::Bytes(bytes),
-        ron_value::Value::Option(value) => {
-            Value::Option(value.map(|v| Box::new(ron_value_to_value(*v))))
-        }
-        ron_value::Value::List(values) => {
-            Value::Array(values.into_iter().map(ron_value_to_value).collect())
-        }
+        ron_value::Value::Option(value) => Value::Option(match value {
+            Some(value) => Some(Box::new(ron_value_to_value(*value)?)),
+            None => None,
+        }),
+        ron_value::Value::List(values) => Value::Array(ron_values_to_values(values)?),
         ron_value::Value::Map(map) => {
             let mut map2 = Map::new();
 
             for (key, value) in map {
                 let ron_value::Value::String(key) = key else {
-                    panic!("key is not a string")
+                    bail!(
+                        "the map key {} is not a string",
+                        ron_value::to_string(&key)?
+                    )
                 };
 
-                map2.0.insert(key, ron_value_to_value(value));
+                map2.0.insert(key, ron_value_to_value(value)?);
             }
 
             Value::Map(map2)
         }
-        ron_value::Value::Tuple(values) => {
-            Value::Tuple(values.into_iter().map(ron_value_to_value).collect())
-        }
+        ron_value::Value::Tuple(values) => Value::Tuple(ron_values_to_values(values)?),
         ron_value::Value::UnitStruct(name) => Value::UnitStruct(name),
         ron_value::Value::Struct(name, map) => {
             let mut map2 = Map::new();
 
             for (key, value) in map {
-                map2.0.insert(key, ron_value_to_value(value));
+                map2.0.insert(key, ron_value_to_value(value)?);
             }
 
             Value::Struct(name, map2)
         }
         ron_value::Value::NamedTuple(name, values) => {
-            Value::TupleStruct(name, values.into_iter().map(ron_value_to_value).collect())
+            Value::TupleStruct(name, ron_values_to_values(values)?)
         }
+    };
+
+    Ok(value)
+}
+
+fn ron_values_to_values(values: Vec<ron_value::Value>) -> anyhow::Result<Vec<Value>> {
+    values.into_iter().map(ron_value_to_value).collect()
+}
+
+#[derive(Debug)]
+pub struct CosmicRonProvider;
+
+impl Provider for CosmicRonProvider {
+    fn read(&self, path: &Path, root: &RustSchemaRoot) -> Result<Value, ReadError> {
+        read(path, root)
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        root: &RustSchemaRoot,
+        previous: &Value,
+        value: Option<Value>,
+        removed_unknown: &HashSet<String>,
+    ) -> anyhow::Result<()> {
+        write(path, root, previous, value, removed_unknown)
+    }
+
+    fn layout(&self) -> Layout {
+        Layout::Directory
     }
 }
 
-pub fn read(path: &Path) -> anyhow::Result<Value> {
+pub fn read(path: &Path, root: &RustSchemaRoot) -> Result<Value, ReadError> {
     if !path.exists() {
         return Ok(Value::Empty);
     }
 
+    let fields = root_fields(root).map_err(|e| ReadError::new(path, e))?;
+
     let mut map = Map::new();
 
-    for dir_entry in fs::read_dir(path)? {
-        let dir_entry = dir_entry?;
+    for dir_entry in fs::read_dir(path).map_err(|e| ReadError::new(path, e))? {
+        let dir_entry = dir_entry.map_err(|e| ReadError::new(path, e))?;
+
+        let file_path = dir_entry.path();
 
         let filename = dir_entry.file_name();
 
-        let filename = filename.to_str().ok_or(anyhow!("no filename"))?;
+        let filename = filename
+            .to_str()
+            .ok_or_else(|| ReadError::new(&file_path, anyhow!("the filename is not utf8")))?;
 
-        let content = fs::read_to_string(dir_entry.path())?;
+        // temporary files of an interrupted write
+        if filename.starts_with('.') {
+            continue;
+        }
+
+        let content = fs::read_to_string(&file_path).map_err(|e| ReadError::new(&file_path, e))?;
 
         debug!("{}", content);
 
-        let value = ron_value::from_str(&content)?;
+        let document = ron_value::document_from_str(&content).map_err(|e| {
+            ReadError::new(&file_path, anyhow!(e.message()))
+                .with_position(Position {
+                    line: e.line(),
+                    column: e.column(),
+                })
+                .with_snippet(e.snippet())
+        })?;
 
-        let value = ron_value_to_value(value);
+        let value =
+            ron_value_to_value(document.value).map_err(|e| ReadError::new(&file_path, e))?;
+
+        // the tree doesn't know the extensions
+        let value = match fields.get(filename) {
+            Some(field) => ron_extensions::expand(root, &field.schema, value, &document.extensions),
+            None => value,
+        };
 
         debug!("{:?}", value);
 
@@ -158,38 +216,232 @@ pub fn read(path: &Path) -> anyhow::Result<Value> {
     Ok(Value::Struct(None, map))
 }
 
-fn clear_dir(path: &Path) -> std::io::Result<()> {
-    if path.exists() {
-        for entry in fs::read_dir(path)? {
-            let entry = entry?;
-            let p = entry.path();
+/// Only the files of the keys which changed since `previous` was read are written.
+/// A file is removed when its key was reset or removed. The files of the keys
+/// unknown to the schema are only removed if they are in `removed_unknown`.
+pub fn write(
+    path: &Path,
+    root: &RustSchemaRoot,
+    previous: &Value,
+    value: Option<Value>,
+    removed_unknown: &HashSet<String>,
+) -> anyhow::Result<()> {
+    let previous = match previous {
+        Value::Struct(_, map) => map.0.clone(),
+        Value::Empty => IndexMap::new(),
+        _ => bail!("previous value is not a struct"),
+    };
 
-            if p.is_file() {
-                fs::remove_file(p)?;
-            }
+    let map = match value {
+        Some(Value::Struct(_, map)) => map.0,
+        Some(_) => bail!("initial value is not a struct"),
+        None => IndexMap::new(),
+    };
+
+    // staged, so an error leaves all the files as they were
+    let mut transaction = Transaction::new();
+
+    let fields = root_fields(root)?;
+
+    for key in previous.keys() {
+        if !map.contains_key(key) && (fields.contains_key(key) || removed_unknown.contains(key)) {
+            transaction.remove(&path.join(key));
+        }
+    }
+
+    for (key, value) in map {
+        // the numbers of the tree have the kinds of the schema
+        if previous
+            .get(&key)
+            .is_some_and(|previous| previous.equivalent(&value))
+        {
+            continue;
         }
+
+        let file_path = path.join(&key);
+        let previous = fs::read_to_string(&file_path).ok();
+
+        // written like the existing file, which can enable extensions
+        let extensions = previous
+            .as_deref()
+            .and_then(|previous| ron_value::document_from_str(previous).ok())
+            .map(|document| document.extensions)
+            .unwrap_or_default();
+
+        let value = match fields.get(&key) {
+            Some(field) => ron_extensions::collapse(root, &field.schema, value, &extensions),
+            None => value,
+        };
+        let value = value_to_ron_value(value);
+        let pretty_config = PrettyConfig::new().extensions(extensions);
+
+        // keep the comments and the layout of the existing file
+        let content = match previous {
+            Some(previous) => match ron_value::edit(&previous, &value) {
+                Ok(content) if content == previous => continue,
+                Ok(content) => content,
+                Err(e) => {
+                    warn!("can't edit {}, rewrite it: {e}", file_path.display());
+                    ron_value::to_string_pretty(&value, &pretty_config)?
+                }
+            },
+            // like cosmic-config
+            None => ron_value::to_string_pretty(&value, &pretty_config)?,
+        };
+
+        transaction.write(&file_path, content.as_bytes())?;
     }
 
-    Ok(())
+    transaction.commit()
 }
 
-pub fn write(path: &Path, value: Option<Value>) -> anyhow::Result<()> {
-    let value = value.map(value_to_ron_value);
+fn root_fields(root: &RustSchemaRoot) -> anyhow::Result<&IndexMap<String, StructField>> {
+    match &root.resolve_schema(&root.schema)?.kind {
+        RustSchemaKind::Struct(struct_) => Ok(&struct_.fields),
+        _ => bail!("the schema is not a struct"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_schema2::{RustSchema, schema_for};
+
+    use super::*;
+    use crate::providers::tests::temp_dir;
+
+    #[derive(RustSchema)]
+    #[allow(dead_code)]
+    struct Config {
+        a: u32,
+    }
+
+    #[derive(RustSchema)]
+    #[allow(dead_code)]
+    struct Newtype(u32);
+
+    #[derive(RustSchema)]
+    #[allow(dead_code)]
+    struct Extended {
+        b: Newtype,
+        c: Option<u32>,
+    }
 
-    clear_dir(path)?;
+    #[derive(RustSchema)]
+    #[allow(dead_code)]
+    struct Unit;
 
-    if let Some(value) = value {
-        let map = if let ron_value::Value::Struct(_, map) = value {
-            map
-        } else {
-            bail!("initial value is not a struct")
+    #[derive(RustSchema)]
+    #[allow(dead_code)]
+    struct Braced {}
+
+    #[derive(RustSchema)]
+    #[allow(dead_code)]
+    struct Structs {
+        unit: Unit,
+        braced: Braced,
+    }
+
+    #[test]
+    fn keep_unknown_files() {
+        let path = temp_dir();
+
+        fs::write(path.join("a"), "1").unwrap();
+        fs::write(path.join("newer"), "true").unwrap();
+        fs::write(path.join("removed"), "true").unwrap();
+
+        let root = schema_for::<Config>();
+        let previous = read(&path, &root).unwrap();
+
+        // like the tree after a reset of the page
+        write(
+            &path,
+            &root,
+            &previous,
+            None,
+            &HashSet::from(["removed".to_string()]),
+        )
+        .unwrap();
+
+        assert!(!path.join("a").exists());
+        assert!(path.join("newer").exists());
+        assert!(!path.join("removed").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn extensions() {
+        let path = temp_dir();
+
+        fs::write(path.join("b"), "#![enable(unwrap_newtypes)]\n2").unwrap();
+        fs::write(path.join("c"), "#![enable(implicit_some)]\n1").unwrap();
+
+        let root = schema_for::<Extended>();
+        let previous = read(&path, &root).unwrap();
+
+        let Value::Struct(_, map) = &previous else {
+            panic!("not a struct: {previous:?}");
         };
+        assert!(matches!(&map.0["b"], Value::Tuple(values) if values.len() == 1));
+        assert!(matches!(&map.0["c"], Value::Option(Some(_))));
 
-        for (key, value) in map {
-            let content = ron_value::to_string(&value).unwrap();
-            write_and_create_parent(&path.join(key), content.as_bytes())?;
-        }
+        let mut value = Map::new();
+        value.0.insert(
+            "b".to_string(),
+            Value::Tuple(vec![Value::Number(Number::U32(3))]),
+        );
+        value.0.insert(
+            "c".to_string(),
+            Value::Option(Some(Box::new(Value::Number(Number::U32(4))))),
+        );
+
+        write(
+            &path,
+            &root,
+            &previous,
+            Some(Value::Struct(None, value)),
+            &HashSet::new(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            fs::read_to_string(path.join("b")).unwrap(),
+            "#![enable(unwrap_newtypes)]\n3"
+        );
+        assert_eq!(
+            fs::read_to_string(path.join("c")).unwrap(),
+            "#![enable(implicit_some)]\n4"
+        );
+
+        fs::remove_dir_all(&path).unwrap();
     }
 
-    Ok(())
+    #[test]
+    fn unit_and_braced_structs() {
+        let path = temp_dir();
+
+        fs::write(path.join("unit"), "Unit").unwrap();
+        fs::write(path.join("braced"), "()").unwrap();
+
+        let root = schema_for::<Structs>();
+        let value = read(&path, &root).unwrap();
+
+        let schema = root.resolve_schema(&root.schema).unwrap();
+        let mut tree = NodeContainer::from_schema_and_value(&root, schema, &value, &Value::Empty);
+        tree.set_modified_from_value(&value);
+
+        let Node::Struct(node_struct) = &tree.node else {
+            panic!("not a struct: {tree:?}");
+        };
+        assert!(matches!(node_struct.fields["unit"].node, Node::Unit(_)));
+        assert!(matches!(node_struct.fields["braced"].node, Node::Struct(_)));
+
+        let Some(Value::Struct(_, map)) = tree.to_value() else {
+            panic!("no value");
+        };
+        assert_eq!(map.0["unit"], Value::UnitStruct("Unit".to_string()));
+        assert!(matches!(&map.0["braced"], Value::Struct(_, fields) if fields.0.is_empty()));
+
+        fs::remove_dir_all(&path).unwrap();
+    }
 }
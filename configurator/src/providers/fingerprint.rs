@@ -0,0 +1,139 @@
+//! State of the files of a config when it was read, to know if
+//! someone else changed them before we write over them.
+
+use std::{
+    collections::BTreeMap,
+    fs::{self, Metadata},
+    hash::{DefaultHasher, Hash, Hasher},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use super::Layout;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct FileFingerprint {
+    modified: Option<SystemTime>,
+    len: u64,
+    hash: u64,
+}
+
+impl FileFingerprint {
+    fn read(path: &Path, metadata: &Metadata) -> Option<Self> {
+        let content = fs::read(path).ok()?;
+
+        let mut hasher = DefaultHasher::new();
+        content.hash(&mut hasher);
+
+        Some(Self {
+            modified: metadata.modified().ok(),
+            len: metadata.len(),
+            hash: hasher.finish(),
+        })
+    }
+
+    fn same_metadata(&self, metadata: &Metadata) -> bool {
+        self.modified.is_some()
+            && self.modified == metadata.modified().ok()
+            && self.len == metadata.len()
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fingerprint {
+    files: BTreeMap<PathBuf, FileFingerprint>,
+}
+
+/// Files of the config at these paths, with their metadata.
+/// Hidden files are skipped, like when the config is read.
+fn files<'a>(
+    paths: impl IntoIterator<Item = &'a Path>,
+    layout: Layout,
+) -> BTreeMap<PathBuf, Metadata> {
+    fn visit(path: &Path, files: &mut BTreeMap<PathBuf, Metadata>) {
+        let Ok(metadata) = fs::metadata(path) else {
+            return;
+        };
+
+        if metadata.is_dir() {
+            for dir_entry in fs::read_dir(path).into_iter().flatten().flatten() {
+                if !dir_entry.file_name().to_string_lossy().starts_with('.') {
+                    visit(&dir_entry.path(), files);
+                }
+            }
+        } else {
+            files.insert(path.to_path_buf(), metadata);
+        }
+    }
+
+    let mut files = BTreeMap::new();
+
+    for path in paths {
+        match layout {
+            Layout::File => {
+                if let Ok(metadata) = fs::metadata(path)
+                    && metadata.is_file()
+                {
+                    files.insert(path.to_path_buf(), metadata);
+                }
+            }
+            Layout::Directory => visit(path, &mut files),
+        }
+    }
+
+    files
+}
+
+impl Fingerprint {
+    pub fn read<'a>(paths: impl IntoIterator<Item = &'a Path>, layout: Layout) -> Self {
+        let files = files(paths, layout)
+            .into_iter()
+            .filter_map(|(path, metadata)| {
+                let fingerprint = FileFingerprint::read(&path, &metadata)?;
+                Some((path, fingerprint))
+            })
+            .collect();
+
+        Self { files }
+    }
+
+    /// Take the current state of the files after we wrote them.
+    /// Only the files whose metadata changed are read again.
+    pub fn update<'a>(&mut self, paths: impl IntoIterator<Item = &'a Path>, layout: Layout) {
+        self.files = files(paths, layout)
+            .into_iter()
+            .filter_map(|(path, metadata)| {
+                let fingerprint = match self.files.remove(&path) {
+                    Some(fingerprint) if fingerprint.same_metadata(&metadata) => fingerprint,
+                    _ => FileFingerprint::read(&path, &metadata)?,
+                };
+                Some((path, fingerprint))
+            })
+            .collect();
+    }
+
+    /// Whether the files are not the ones we read anymore.
+    /// The content is only compared when the modification time changed.
+    pub fn is_outdated<'a>(
+        &self,
+        paths: impl IntoIterator<Item = &'a Path>,
+        layout: Layout,
+    ) -> bool {
+        let files = files(paths, layout);
+
+        if files.len() != self.files.len() {
+            return true;
+        }
+
+        files
+            .iter()
+            .any(|(path, metadata)| match self.files.get(path) {
+                Some(fingerprint) => {
+                    !fingerprint.same_metadata(metadata)
+                        && FileFingerprint::read(path, metadata)
+                            .is_none_or(|new| new.hash != fingerprint.hash)
+                }
+                None => true,
+            })
+    }
+}
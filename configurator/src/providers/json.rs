@@ -0,0 +1,665 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use anyhow::{anyhow, bail};
+use indexmap::IndexMap;
+use rust_schema2::{
+    EnumVariantKind, RustSchema, RustSchemaKind, RustSchemaOrRef, RustSchemaRoot, StructField,
+};
+
+use crate::{
+    generic_value::{F64, Map, Number, Value},
+    providers::{Position, Provider, ReadError, atomic},
+};
+
+// JSON doesn't tell apart structs, maps, tuples and enums, so we follow the schema,
+// using the encoding of serde_json: newtypes are transparent, enums are externally tagged,
+// and `None`, `()` and unit structs are `null`.
+
+#[derive(Debug)]
+pub struct JsonProvider;
+
+impl Provider for JsonProvider {
+    fn read(&self, path: &Path, root: &RustSchemaRoot) -> Result<Value, ReadError> {
+        read(path, root)
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        root: &RustSchemaRoot,
+        previous: &Value,
+        value: Option<Value>,
+        removed_unknown: &HashSet<String>,
+    ) -> anyhow::Result<()> {
+        write(path, root, value)
+    }
+}
+
+pub fn read(path: &Path, root: &RustSchemaRoot) -> Result<Value, ReadError> {
+    if !path.exists() {
+        return Ok(Value::Empty);
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| ReadError::new(path, e))?;
+
+    let json_value: json::Value = json::from_str(&content).map_err(|e| {
+        let position = Position {
+            line: e.line(),
+            column: e.column(),
+        };
+        ReadError::new(path, e).with_position(position)
+    })?;
+
+    let schema = root
+        .resolve_schema(&root.schema)
+        .map_err(|e| ReadError::new(path, e))?;
+
+    json_to_value(root, Some(schema), json_value).map_err(|e| ReadError::new(path, e))
+}
+
+pub fn write(path: &Path, root: &RustSchemaRoot, value: Option<Value>) -> anyhow::Result<()> {
+    match value {
+        Some(value) => {
+            let schema = root.resolve_schema(&root.schema)?;
+
+            let json_value = value_to_json(root, Some(schema), value)?;
+
+            let content = json::to_string_pretty(&json_value)?;
+            atomic::write(path, content.as_bytes())?;
+        }
+        None => atomic::remove(path)?,
+    }
+
+    Ok(())
+}
+
+fn kind_name(kind: &RustSchemaKind) -> &'static str {
+    match kind {
+        RustSchemaKind::Unit => "unit",
+        RustSchemaKind::Boolean => "boolean",
+        RustSchemaKind::Number(_) => "number",
+        RustSchemaKind::Char => "char",
+        RustSchemaKind::String => "string",
+        RustSchemaKind::Option(_) => "option",
+        RustSchemaKind::Array(_) => "array",
+        RustSchemaKind::Tuple(_) => "tuple",
+        RustSchemaKind::Map(_) => "map",
+        RustSchemaKind::Struct(_) => "struct",
+        RustSchemaKind::TupleStruct(_) => "tuple struct",
+        RustSchemaKind::Enum(_) => "enum",
+    }
+}
+
+/// Convert a json value, following `schema`.
+/// Without schema (unknown field), objects are read as maps.
+pub fn json_to_value(
+    root: &RustSchemaRoot,
+    schema: Option<&RustSchema>,
+    json_value: json::Value,
+) -> anyhow::Result<Value> {
+    let Some(schema) = schema else {
+        return json_to_value_untyped(json_value);
+    };
+
+    let value = match (&schema.kind, json_value) {
+        (RustSchemaKind::Unit, json::Value::Null) => Value::Unit,
+        (RustSchemaKind::Boolean, json::Value::Bool(b)) => Value::Bool(b),
+        // the kind of the schema is applied when building the node
+        (RustSchemaKind::Number(_), json::Value::Number(n)) => Value::Number(number_from_json(&n)?),
+        (RustSchemaKind::Char, json::Value::String(s)) => {
+            let mut chars = s.chars();
+
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Value::Char(c),
+                _ => bail!("{:?} is not a single character", s),
+            }
+        }
+        (RustSchemaKind::String, json::Value::String(s)) => Value::String(s),
+        (RustSchemaKind::Option(_), json::Value::Null) => Value::Option(None),
+        (RustSchemaKind::Option(inner), json_value) => {
+            let inner = root.resolve_schema(inner)?;
+            Value::Option(Some(Box::new(json_to_value(
+                root,
+                Some(inner),
+                json_value,
+            )?)))
+        }
+        (RustSchemaKind::Array(array), json::Value::Array(values)) => {
+            let template = match &array.template {
+                Some(template) => Some(root.resolve_schema(template)?),
+                None => None,
+            };
+
+            Value::Array(
+                values
+                    .into_iter()
+                    .map(|value| json_to_value(root, template, value))
+                    .collect::<anyhow::Result<_>>()?,
+            )
+        }
+        (RustSchemaKind::Tuple(fields), json::Value::Array(values)) => {
+            Value::Tuple(tuple_from_json(root, fields, values)?)
+        }
+        (RustSchemaKind::Map(value_schema), json::Value::Object(object)) => {
+            let value_schema = root.resolve_schema(value_schema)?;
+
+            let mut map = Map::with_capacity(object.len());
+
+            for (key, value) in object {
+                map.0
+                    .insert(key, json_to_value(root, Some(value_schema), value)?);
+            }
+
+            Value::Map(map)
+        }
+        (RustSchemaKind::Struct(s), json::Value::Null) if s.unit => {
+            Value::UnitStruct(s.name.clone())
+        }
+        (RustSchemaKind::Struct(s), json::Value::Object(object)) => {
+            Value::Struct(None, fields_from_json(root, &s.fields, object)?)
+        }
+        (RustSchemaKind::TupleStruct(s), json_value) if s.fields.len() == 1 => {
+            let field = root.resolve_schema(&s.fields[0])?;
+            Value::TupleStruct(
+                s.name.clone(),
+                vec![json_to_value(root, Some(field), json_value)?],
+            )
+        }
+        (RustSchemaKind::TupleStruct(s), json::Value::Array(values)) => {
+            Value::TupleStruct(s.name.clone(), tuple_from_json(root, &s.fields, values)?)
+        }
+        (RustSchemaKind::Enum(e), json::Value::String(name)) => {
+            let variant = e
+                .variants
+                .iter()
+                .find(|variant| variant.name == name)
+                .ok_or_else(|| anyhow!("{} is not a variant of {}", name, e.name))?;
+
+            if !matches!(variant.kind, EnumVariantKind::Unit) {
+                bail!("variant {} of {} has a payload", name, e.name);
+            }
+
+            Value::UnitStruct(name)
+        }
+        (RustSchemaKind::Enum(e), json::Value::Object(object)) if object.len() == 1 => {
+            let (name, payload) = object.into_iter().next().unwrap();
+
+            let variant = e
+                .variants
+                .iter()
+                .find(|variant| variant.name == name)
+                .ok_or_else(|| anyhow!("{} is not a variant of {}", name, e.name))?;
+
+            match (&variant.kind, payload) {
+                (EnumVariantKind::Tuple(fields), payload) if fields.len() == 1 => {
+                    let field = root.resolve_schema(&fields[0])?;
+                    Value::TupleStruct(name, vec![json_to_value(root, Some(field), payload)?])
+                }
+                (EnumVariantKind::Tuple(fields), json::Value::Array(values)) => {
+                    Value::TupleStruct(name, tuple_from_json(root, fields, values)?)
+                }
+                (EnumVariantKind::Struct(fields), json::Value::Object(object)) => {
+                    Value::Struct(Some(name), fields_from_json(root, fields, object)?)
+                }
+                (_, payload) => bail!("{} is not a valid payload for {}", payload, name),
+            }
+        }
+        (kind, json_value) => bail!("expected {}, found {}", kind_name(kind), json_value),
+    };
+
+    Ok(value)
+}
+
+fn json_to_value_untyped(json_value: json::Value) -> anyhow::Result<Value> {
+    let value = match json_value {
+        json::Value::Null => Value::Unit,
+        json::Value::Bool(b) => Value::Bool(b),
+        json::Value::Number(n) => Value::Number(number_from_json(&n)?),
+        json::Value::String(s) => Value::String(s),
+        json::Value::Array(values) => Value::Array(
+            values
+                .into_iter()
+                .map(json_to_value_untyped)
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        json::Value::Object(object) => Value::Map(Map(object
+            .into_iter()
+            .map(|(key, value)| Ok((key, json_to_value_untyped(value)?)))
+            .collect::<anyhow::Result<_>>()?)),
+    };
+
+    Ok(value)
+}
+
+fn tuple_from_json(
+    root: &RustSchemaRoot,
+    fields: &[RustSchemaOrRef],
+    values: Vec<json::Value>,
+) -> anyhow::Result<Vec<Value>> {
+    if fields.len() != values.len() {
+        bail!("expected {} values, found {}", fields.len(), values.len());
+    }
+
+    fields
+        .iter()
+        .zip(values)
+        .map(|(field, value)| json_to_value(root, Some(root.resolve_schema(field)?), value))
+        .collect()
+}
+
+fn fields_from_json(
+    root: &RustSchemaRoot,
+    fields: &IndexMap<String, StructField>,
+    object: json::Map<String, json::Value>,
+) -> anyhow::Result<Map<String, Value>> {
+    let mut map = Map::with_capacity(object.len());
+
+    for (key, value) in object {
+        let schema = match fields.get(&key) {
+            Some(field) => Some(root.resolve_schema(&field.schema)?),
+            None => None,
+        };
+
+        let value = json_to_value(root, schema, value)?;
+        map.0.insert(key, value);
+    }
+
+    Ok(map)
+}
+
+fn number_from_json(n: &json::Number) -> anyhow::Result<Number> {
+    if let Some(v) = n.as_u64() {
+        Ok(Number::U64(v))
+    } else if let Some(v) = n.as_i64() {
+        Ok(Number::I64(v))
+    } else if let Some(v) = n.as_f64() {
+        Ok(Number::F64(F64(v)))
+    } else {
+        bail!("{} is not a valid number", n)
+    }
+}
+
+fn number_to_json(number: Number) -> anyhow::Result<json::Number> {
+    let float = match number {
+        // go through the text representation, to not write 0.1 as 0.10000000149011612
+        Number::F32(v) => Some(v.0.to_string().parse::<f64>()?),
+        Number::F64(v) => Some(v.0),
+        _ => None,
+    };
+
+    if let Some(float) = float {
+        return json::Number::from_f64(float)
+            .ok_or_else(|| anyhow!("{} can't be written in json", float));
+    }
+
+    if let Some(v) = number.as_u128().and_then(|v| u64::try_from(v).ok()) {
+        Ok(v.into())
+    } else if let Some(v) = number.as_i128().and_then(|v| i64::try_from(v).ok()) {
+        Ok(v.into())
+    } else {
+        bail!("{} is out of range for json", number)
+    }
+}
+
+/// Convert a value to json, following `schema`
+pub fn value_to_json(
+    root: &RustSchemaRoot,
+    schema: Option<&RustSchema>,
+    value: Value,
+) -> anyhow::Result<json::Value> {
+    let Some(schema) = schema else {
+        return value_to_json_untyped(value);
+    };
+
+    let json_value = match (&schema.kind, value) {
+        (RustSchemaKind::Unit, Value::Unit) => json::Value::Null,
+        // `()` is parsed as an empty tuple
+        (RustSchemaKind::Unit, Value::Tuple(values)) if values.is_empty() => json::Value::Null,
+        (RustSchemaKind::Boolean, Value::Bool(b)) => json::Value::Bool(b),
+        (RustSchemaKind::Number(_), Value::Number(n)) => json::Value::Number(number_to_json(n)?),
+        (RustSchemaKind::Char, Value::Char(c)) => json::Value::String(c.to_string()),
+        (RustSchemaKind::String, Value::String(s)) => json::Value::String(s),
+        (RustSchemaKind::Option(_), Value::Option(None)) => json::Value::Null,
+        (RustSchemaKind::Option(inner), Value::Option(Some(value))) => {
+            value_to_json(root, Some(root.resolve_schema(inner)?), *value)?
+        }
+        (RustSchemaKind::Option(inner), value) => {
+            value_to_json(root, Some(root.resolve_schema(inner)?), value)?
+        }
+        (RustSchemaKind::Array(array), Value::Array(values)) => {
+            let template = match &array.template {
+                Some(template) => Some(root.resolve_schema(template)?),
+                None => None,
+            };
+
+            json::Value::Array(
+                values
+                    .into_iter()
+                    .map(|value| value_to_json(root, template, value))
+                    .collect::<anyhow::Result<_>>()?,
+            )
+        }
+        (RustSchemaKind::Tuple(fields), Value::Tuple(values)) => {
+            json::Value::Array(tuple_to_json(root, fields, values)?)
+        }
+        (RustSchemaKind::Map(value_schema), Value::Map(map)) => {
+            let value_schema = root.resolve_schema(value_schema)?;
+
+            let mut object = json::Map::new();
+
+            for (key, value) in map.0 {
+                object.insert(key, value_to_json(root, Some(value_schema), value)?);
+            }
+
+            json::Value::Object(object)
+        }
+        (RustSchemaKind::Struct(s), Value::UnitStruct(_)) if s.unit => json::Value::Null,
+        (RustSchemaKind::Struct(s), Value::Struct(_, map)) => {
+            json::Value::Object(fields_to_json(root, &s.fields, map)?)
+        }
+        (RustSchemaKind::TupleStruct(s), Value::TupleStruct(_, mut values))
+            if s.fields.len() == 1 && values.len() == 1 =>
+        {
+            let field = root.resolve_schema(&s.fields[0])?;
+            value_to_json(root, Some(field), values.remove(0))?
+        }
+        (RustSchemaKind::TupleStruct(s), Value::TupleStruct(_, values)) => {
+            json::Value::Array(tuple_to_json(root, &s.fields, values)?)
+        }
+        (RustSchemaKind::Enum(e), value)
+            if let Some(variant) = value
+                .enum_variant_name()
+                .and_then(|name| e.variants.iter().find(|variant| variant.name == name)) =>
+        {
+            let payload = match (&variant.kind, value) {
+                (EnumVariantKind::Unit, Value::UnitStruct(name)) => {
+                    return Ok(json::Value::String(name));
+                }
+                (EnumVariantKind::Tuple(fields), Value::TupleStruct(_, mut values))
+                    if fields.len() == 1 && values.len() == 1 =>
+                {
+                    let field = root.resolve_schema(&fields[0])?;
+                    value_to_json(root, Some(field), values.remove(0))?
+                }
+                (EnumVariantKind::Tuple(fields), Value::TupleStruct(_, values)) => {
+                    json::Value::Array(tuple_to_json(root, fields, values)?)
+                }
+                (EnumVariantKind::Struct(fields), Value::Struct(_, map)) => {
+                    json::Value::Object(fields_to_json(root, fields, map)?)
+                }
+                (_, value) => bail!("{:?} is not a valid value for {}", value, variant.name),
+            };
+
+            let mut object = json::Map::new();
+            object.insert(variant.name.clone(), payload);
+            json::Value::Object(object)
+        }
+        (kind, value) => bail!("expected {}, found {:?}", kind_name(kind), value),
+    };
+
+    Ok(json_value)
+}
+
+fn value_to_json_untyped(value: Value) -> anyhow::Result<json::Value> {
+    let json_value = match value {
+        Value::Empty => bail!("empty value"),
+        Value::Unit | Value::Option(None) => json::Value::Null,
+        Value::Bool(b) => json::Value::Bool(b),
+        Value::Char(c) => json::Value::String(c.to_string()),
+        Value::Number(n) => json::Value::Number(number_to_json(n)?),
+        Value::String(s) => json::Value::String(s),
+        Value::Bytes(bytes) => {
+            json::Value::Array(bytes.into_iter().map(json::Value::from).collect())
+        }
+        Value::Option(Some(value)) => value_to_json_untyped(*value)?,
+        Value::Array(values) | Value::Tuple(values) => json::Value::Array(
+            values
+                .into_iter()
+                .map(value_to_json_untyped)
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Value::Map(map) | Value::Struct(_, map) => json::Value::Object(
+            map.0
+                .into_iter()
+                .map(|(key, value)| Ok((key, value_to_json_untyped(value)?)))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        Value::UnitStruct(name) => json::Value::String(name),
+        Value::TupleStruct(_, mut values) if values.len() == 1 => {
+            value_to_json_untyped(values.remove(0))?
+        }
+        Value::TupleStruct(_, values) => json::Value::Array(
+            values
+                .into_iter()
+                .map(value_to_json_untyped)
+                .collect::<anyhow::Result<_>>()?,
+        ),
+    };
+
+    Ok(json_value)
+}
+
+fn tuple_to_json(
+    root: &RustSchemaRoot,
+    fields: &[RustSchemaOrRef],
+    values: Vec<Value>,
+) -> anyhow::Result<Vec<json::Value>> {
+    if fields.len() != values.len() {
+        bail!("expected {} values, found {}", fields.len(), values.len());
+    }
+
+    fields
+        .iter()
+        .zip(values)
+        .map(|(field, value)| value_to_json(root, Some(root.resolve_schema(field)?), value))
+        .collect()
+}
+
+fn fields_to_json(
+    root: &RustSchemaRoot,
+    fields: &IndexMap<String, StructField>,
+    mut map: Map<String, Value>,
+) -> anyhow::Result<json::Map<String, json::Value>> {
+    let mut object = json::Map::new();
+
+    // keep the order of the schema
+    for (key, field) in fields {
+        if let Some(value) = map.0.shift_remove(key) {
+            let schema = root.resolve_schema(&field.schema)?;
+            object.insert(key.clone(), value_to_json(root, Some(schema), value)?);
+        }
+    }
+
+    // unknown keys
+    for (key, value) in map.0 {
+        object.insert(key, value_to_json(root, None, value)?);
+    }
+
+    Ok(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use rust_schema2::{RustSchemaTrait, schema_for};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::providers::tests::temp_dir;
+
+    #[derive(RustSchema, Serialize, Deserialize, Debug, PartialEq)]
+    struct Unit;
+
+    #[derive(RustSchema, Serialize, Deserialize, Debug, PartialEq)]
+    struct Newtype(u32);
+
+    #[derive(RustSchema, Serialize, Deserialize, Debug, PartialEq)]
+    struct Inner {
+        a: i32,
+        b: String,
+    }
+
+    #[derive(RustSchema, Serialize, Deserialize, Debug, PartialEq)]
+    enum Enum {
+        Unit,
+        Newtype(Inner),
+        Tuple(u8, bool),
+        Struct { c: char },
+    }
+
+    #[derive(RustSchema, Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        unit: Unit,
+        newtype: Newtype,
+        none: Option<u32>,
+        some: Option<Inner>,
+        map: BTreeMap<String, u32>,
+        inner: Inner,
+        tuple: (f64, char),
+        enums: Vec<Enum>,
+    }
+
+    /// Read the json of `config`, and write it back
+    fn roundtrip<T: RustSchemaTrait + Serialize>(config: &T) -> (Value, json::Value) {
+        let root = schema_for::<T>();
+        let schema = root.resolve_schema(&root.schema).unwrap();
+
+        let json_value = json::to_value(config).unwrap();
+        let value = json_to_value(&root, Some(schema), json_value.clone()).unwrap();
+        let written = value_to_json(&root, Some(schema), value.clone()).unwrap();
+
+        assert_eq!(written, json_value);
+
+        (value, written)
+    }
+
+    #[test]
+    fn schema_roundtrip() {
+        let config = Config {
+            unit: Unit,
+            newtype: Newtype(1),
+            none: None,
+            some: Some(Inner {
+                a: -1,
+                b: "b".to_string(),
+            }),
+            map: BTreeMap::from([("b".to_string(), 2), ("a".to_string(), 1)]),
+            inner: Inner {
+                a: 2,
+                b: String::new(),
+            },
+            tuple: (0.5, 'c'),
+            enums: vec![
+                Enum::Unit,
+                Enum::Newtype(Inner {
+                    a: 3,
+                    b: "c".to_string(),
+                }),
+                Enum::Tuple(4, true),
+                Enum::Struct { c: 'd' },
+            ],
+        };
+
+        let (value, written) = roundtrip(&config);
+
+        assert_eq!(json::from_value::<Config>(written).unwrap(), config);
+
+        let Value::Struct(_, fields) = value else {
+            panic!("not a struct: {value:?}");
+        };
+
+        assert_eq!(fields.0["unit"], Value::UnitStruct("Unit".to_string()));
+        assert!(
+            matches!(&fields.0["newtype"], Value::TupleStruct(name, values) if name == "Newtype" && values.len() == 1)
+        );
+        assert_eq!(fields.0["none"], Value::Option(None));
+        assert!(
+            matches!(&fields.0["some"], Value::Option(Some(inner)) if matches!(**inner, Value::Struct(..)))
+        );
+        // objects are read as the schema says
+        assert!(matches!(&fields.0["map"], Value::Map(_)));
+        assert!(matches!(&fields.0["inner"], Value::Struct(None, _)));
+        assert!(matches!(&fields.0["tuple"], Value::Tuple(values) if values.len() == 2));
+
+        let Value::Array(enums) = &fields.0["enums"] else {
+            panic!("not an array: {:?}", fields.0["enums"]);
+        };
+
+        assert_eq!(enums[0], Value::UnitStruct("Unit".to_string()));
+        assert!(
+            matches!(&enums[1], Value::TupleStruct(name, values) if name == "Newtype" && values.len() == 1)
+        );
+        assert!(
+            matches!(&enums[2], Value::TupleStruct(name, values) if name == "Tuple" && values.len() == 2)
+        );
+        assert!(matches!(&enums[3], Value::Struct(Some(name), _) if name == "Struct"));
+    }
+
+    #[test]
+    fn unknown_fields() {
+        let root = schema_for::<Inner>();
+        let schema = root.resolve_schema(&root.schema).unwrap();
+
+        let json_value = json::json!({ "unknown": { "d": [1, null] }, "b": "b", "a": 1 });
+        let value = json_to_value(&root, Some(schema), json_value).unwrap();
+
+        let Value::Struct(_, fields) = &value else {
+            panic!("not a struct: {value:?}");
+        };
+        assert!(matches!(&fields.0["unknown"], Value::Map(_)));
+
+        // the known fields are written in the order of the schema, then the unknown ones
+        let written = value_to_json(&root, Some(schema), value).unwrap();
+        assert_eq!(
+            json::to_string(&written).unwrap(),
+            r#"{"a":1,"b":"b","unknown":{"d":[1,null]}}"#
+        );
+    }
+
+    #[test]
+    fn invalid_values() {
+        let root = schema_for::<Enum>();
+        let schema = root.resolve_schema(&root.schema).unwrap();
+
+        for json_value in [
+            json::json!("Other"),
+            json::json!("Tuple"),
+            json::json!({ "Unit": null, "Tuple": [1, true] }),
+            json::json!({ "Tuple": [1] }),
+            json::json!({ "Struct": { "c": "cd" } }),
+        ] {
+            assert!(
+                json_to_value(&root, Some(schema), json_value.clone()).is_err(),
+                "{json_value} was accepted"
+            );
+        }
+    }
+
+    #[test]
+    fn write_and_remove() {
+        let path = temp_dir().join("config.json");
+        let root = schema_for::<Inner>();
+
+        let config = Inner {
+            a: 1,
+            b: "b".to_string(),
+        };
+        let value = json_to_value(
+            &root,
+            Some(root.resolve_schema(&root.schema).unwrap()),
+            json::to_value(&config).unwrap(),
+        )
+        .unwrap();
+
+        write(&path, &root, Some(value.clone())).unwrap();
+        assert!(read(&path, &root).unwrap().equivalent(&value));
+
+        // a config without values is removed
+        write(&path, &root, None).unwrap();
+        assert!(!path.exists());
+        write(&path, &root, None).unwrap();
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
@@ -1,45 +1,190 @@
-use anyhow::anyhow;
-use std::{fs, path::Path};
+use rust_schema2::RustSchemaRoot;
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt::{self, Debug},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use configurator_utils::ConfigFormat;
 
 use crate::generic_value::Value;
 
+pub use fingerprint::Fingerprint;
+
+mod atomic;
 pub mod cosmic_ron;
-// #[cfg(test)]
-// mod tests;
+mod fingerprint;
+pub mod json;
+mod ron_extensions;
+#[cfg(test)]
+pub mod tests;
+pub mod toml;
+
+/// Position of a parse error, starting at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub line: usize,
+    pub column: usize,
+}
 
-#[instrument(skip_all)]
-pub fn read_from_format(path: &Path, format: &ConfigFormat) -> Value {
-    debug!("{:?}:{}", path, format);
+impl Position {
+    /// `offset` is counted in chars
+    pub fn from_char_offset(content: &str, offset: usize) -> Self {
+        let mut position = Position { line: 1, column: 1 };
 
-    match format {
-        ConfigFormat::Json => todo!(),
-        ConfigFormat::CosmicRon => cosmic_ron::read(path).unwrap(),
+        for c in content.chars().take(offset) {
+            if c == '\n' {
+                position.line += 1;
+                position.column = 1;
+            } else {
+                position.column += 1;
+            }
+        }
+
+        position
+    }
+
+    /// `offset` is counted in bytes
+    pub fn from_byte_offset(content: &str, offset: usize) -> Self {
+        let offset = content
+            .char_indices()
+            .take_while(|(pos, _)| *pos < offset)
+            .count();
+
+        Self::from_char_offset(content, offset)
     }
 }
 
-pub fn write(path: &Path, format: &ConfigFormat, data: Option<Value>) -> anyhow::Result<()> {
-    // dbg!(&data);
-    match format {
-        ConfigFormat::Json => {
-            // let content = json::to_string_pretty(&data)?;
-            // write_and_create_parent(path, content.as_bytes())?;
-            todo!()
+/// A config file which can't be read
+#[derive(Debug)]
+pub struct ReadError {
+    pub path: PathBuf,
+    /// Set for parse errors, when the format reports it
+    pub position: Option<Position>,
+    pub error: anyhow::Error,
+    /// The lines around the position, with a caret under it
+    pub snippet: Option<String>,
+}
+
+impl ReadError {
+    pub fn new(path: &Path, error: impl Into<anyhow::Error>) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            position: None,
+            error: error.into(),
+            snippet: None,
+        }
+    }
+
+    pub fn with_position(mut self, position: Position) -> Self {
+        self.position = Some(position);
+        self
+    }
+
+    pub fn with_snippet(mut self, snippet: impl Into<String>) -> Self {
+        self.snippet = Some(snippet.into());
+        self
+    }
+}
+
+impl fmt::Display for ReadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.path.display())?;
+
+        if let Some(position) = &self.position {
+            write!(f, ":{}:{}", position.line, position.column)?;
+        }
+
+        write!(f, ": {}", self.error)?;
+
+        if let Some(snippet) = &self.snippet {
+            write!(f, "\n{snippet}")?;
         }
-        ConfigFormat::CosmicRon => cosmic_ron::write(path, data)?,
+
+        Ok(())
     }
+}
 
-    Ok(())
+impl std::error::Error for ReadError {}
+
+/// How a format stores the config on disk
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Layout {
+    /// The whole config is in one file
+    File,
+    /// The path is a directory, with one file per top-level key
+    Directory,
 }
 
-fn write_and_create_parent(path: &Path, contents: &[u8]) -> anyhow::Result<()> {
-    if !path.exists() {
-        let parent = path.parent().ok_or(anyhow!("no parent"))?;
-        fs::create_dir_all(parent)?;
+/// Read and write the config of a format
+pub trait Provider: Debug {
+    /// Return [`Value::Empty`] if there is no config at `path`.
+    /// The schema is needed by formats which don't carry the kind of the values, like json.
+    fn read(&self, path: &Path, root: &RustSchemaRoot) -> Result<Value, ReadError>;
+
+    /// Write `value`, or remove the config if it is [`None`].
+    /// `previous` is the last value read at `path`, used to write only what changed.
+    /// `removed_unknown` are the top-level keys unknown to the schema that the
+    /// user removed: the other unknown keys of `previous` must not be removed.
+    fn write(
+        &self,
+        path: &Path,
+        root: &RustSchemaRoot,
+        previous: &Value,
+        value: Option<Value>,
+        removed_unknown: &HashSet<String>,
+    ) -> anyhow::Result<()>;
+
+    fn layout(&self) -> Layout {
+        Layout::File
     }
+}
+
+/// Providers by the name used in `X_CONFIGURATOR_FORMAT`.
+///
+/// New formats are added by a module of this crate, in [`Registry::default`].
+/// This crate is a binary, so a downstream crate can't register one: that
+/// would need [`Provider`] and [`Value`] to move to a library crate.
+#[derive(Debug)]
+pub struct Registry {
+    providers: BTreeMap<String, Arc<dyn Provider>>,
+}
 
-    fs::write(path, contents)?;
+impl Registry {
+    /// A registry without any provider
+    pub fn empty() -> Self {
+        Self {
+            providers: BTreeMap::new(),
+        }
+    }
 
-    Ok(())
+    /// Replace the provider previously registered with this name
+    pub fn register(&mut self, name: impl Into<String>, provider: impl Provider + 'static) {
+        self.providers.insert(name.into(), Arc::new(provider));
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn Provider>> {
+        self.providers.get(name).cloned()
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.providers.keys().map(String::as_str)
+    }
+}
+
+impl Default for Registry {
+    /// A registry with the providers of this crate
+    fn default() -> Self {
+        let mut registry = Self::empty();
+
+        registry.register(ConfigFormat::Json.to_string(), json::JsonProvider);
+        registry.register(
+            ConfigFormat::CosmicRon.to_string(),
+            cosmic_ron::CosmicRonProvider,
+        );
+        registry.register(ConfigFormat::Toml.to_string(), toml::TomlProvider);
+
+        registry
+    }
 }
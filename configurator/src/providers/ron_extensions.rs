@@ -0,0 +1,240 @@
+//! The extensions of ron change how some values are written, like
+//! `2` instead of `(2)` for a newtype under `unwrap_newtypes`.
+//! The schema says which values are concerned: [`expand`] gives them
+//! the form the tree expects, and [`collapse`] the form of the file.
+
+use ron_value::Extensions;
+use rust_schema2::{EnumVariantKind, RustSchemaKind, RustSchemaOrRef, RustSchemaRoot};
+
+use crate::generic_value::{Map, Value};
+
+/// The value of a file, written with `extensions`, as if they were disabled
+pub fn expand(
+    root: &RustSchemaRoot,
+    schema: &RustSchemaOrRef,
+    value: Value,
+    extensions: &Extensions,
+) -> Value {
+    let Ok(schema) = root.resolve_schema(schema) else {
+        return value;
+    };
+
+    let expand = |schema, value| expand(root, schema, value, extensions);
+
+    match (&schema.kind, value) {
+        (RustSchemaKind::Option(inner), Value::Option(Some(value))) => {
+            Value::Option(Some(Box::new(expand(inner, *value))))
+        }
+        (RustSchemaKind::Option(inner), value)
+            if extensions.implicit_some && value != Value::Option(None) =>
+        {
+            Value::Option(Some(Box::new(expand(inner, value))))
+        }
+        (RustSchemaKind::TupleStruct(tuple_struct), value)
+            if extensions.unwrap_newtypes && tuple_struct.fields.len() == 1 =>
+        {
+            Value::Tuple(vec![expand(&tuple_struct.fields[0], value)])
+        }
+        (RustSchemaKind::Enum(enum_), value) => {
+            let Some(variant) = variant_name(&value)
+                .and_then(|name| enum_.variants.iter().find(|variant| variant.name == name))
+            else {
+                return value;
+            };
+
+            match (&variant.kind, value) {
+                (EnumVariantKind::Tuple(fields), Value::Struct(Some(name), map))
+                    if extensions.unwrap_variant_newtypes && fields.len() == 1 =>
+                {
+                    Value::TupleStruct(name, vec![expand(&fields[0], Value::Struct(None, map))])
+                }
+                (EnumVariantKind::Tuple(fields), Value::TupleStruct(name, values))
+                    if extensions.unwrap_variant_newtypes
+                        && is_merged_in_variant(root, &fields[0], &values) =>
+                {
+                    Value::TupleStruct(name, vec![expand(&fields[0], Value::Tuple(values))])
+                }
+                (EnumVariantKind::Tuple(fields), Value::TupleStruct(name, values)) => {
+                    Value::TupleStruct(name, map_values(fields, values, expand))
+                }
+                (EnumVariantKind::Struct(fields), Value::Struct(name, map)) => Value::Struct(
+                    name,
+                    map_fields(|key| fields.get(key).map(|f| &f.schema), map, expand),
+                ),
+                (_, value) => value,
+            }
+        }
+        (kind, value) => map_children(kind, value, expand),
+    }
+}
+
+/// The value to write in a file with `extensions`, like ron does
+pub fn collapse(
+    root: &RustSchemaRoot,
+    schema: &RustSchemaOrRef,
+    value: Value,
+    extensions: &Extensions,
+) -> Value {
+    let Ok(schema) = root.resolve_schema(schema) else {
+        return value;
+    };
+
+    let collapse = |schema, value| collapse(root, schema, value, extensions);
+
+    match (&schema.kind, value) {
+        // ron keeps `Some` in front of an option
+        (RustSchemaKind::Option(inner), Value::Option(Some(value)))
+            if extensions.implicit_some
+                && !root
+                    .resolve_schema(inner)
+                    .is_ok_and(|inner| matches!(inner.kind, RustSchemaKind::Option(_))) =>
+        {
+            collapse(inner, *value)
+        }
+        (RustSchemaKind::TupleStruct(tuple_struct), Value::Tuple(mut values))
+            if extensions.unwrap_newtypes && values.len() == 1 =>
+        {
+            collapse(&tuple_struct.fields[0], values.remove(0))
+        }
+        (RustSchemaKind::TupleStruct(tuple_struct), Value::Tuple(values))
+            if extensions.explicit_struct_names =>
+        {
+            Value::TupleStruct(
+                tuple_struct.name.clone(),
+                map_values(&tuple_struct.fields, values, collapse),
+            )
+        }
+        (RustSchemaKind::Struct(struct_), Value::Struct(None, map))
+            if extensions.explicit_struct_names =>
+        {
+            Value::Struct(
+                Some(struct_.name.clone()),
+                map_fields(
+                    |key| struct_.fields.get(key).map(|f| &f.schema),
+                    map,
+                    collapse,
+                ),
+            )
+        }
+        (RustSchemaKind::Enum(enum_), value) => {
+            let Some(variant) = variant_name(&value)
+                .and_then(|name| enum_.variants.iter().find(|variant| variant.name == name))
+            else {
+                return value;
+            };
+
+            match (&variant.kind, value) {
+                (EnumVariantKind::Tuple(fields), Value::TupleStruct(name, mut values))
+                    if extensions.unwrap_variant_newtypes && values.len() == 1 =>
+                {
+                    match collapse(&fields[0], values.remove(0)) {
+                        Value::Struct(_, map) => Value::Struct(Some(name), map),
+                        Value::Tuple(values) | Value::TupleStruct(_, values)
+                            if values.len() != 1 =>
+                        {
+                            Value::TupleStruct(name, values)
+                        }
+                        value => Value::TupleStruct(name, vec![value]),
+                    }
+                }
+                (EnumVariantKind::Tuple(fields), Value::TupleStruct(name, values)) => {
+                    Value::TupleStruct(name, map_values(fields, values, collapse))
+                }
+                (EnumVariantKind::Struct(fields), Value::Struct(name, map)) => Value::Struct(
+                    name,
+                    map_fields(|key| fields.get(key).map(|f| &f.schema), map, collapse),
+                ),
+                (_, value) => value,
+            }
+        }
+        (kind, value) => map_children(kind, value, collapse),
+    }
+}
+
+fn variant_name(value: &Value) -> Option<&str> {
+    match value {
+        Value::UnitStruct(name) | Value::TupleStruct(name, _) | Value::Struct(Some(name), _) => {
+            Some(name)
+        }
+        _ => None,
+    }
+}
+
+/// Under `unwrap_variant_newtypes`, a tuple shares the parentheses of its
+/// variant, like `Variant(1, 2)`. Other values keep their own.
+fn is_merged_in_variant(root: &RustSchemaRoot, schema: &RustSchemaOrRef, values: &[Value]) -> bool {
+    values.len() != 1
+        && root.resolve_schema(schema).is_ok_and(|schema| {
+            matches!(
+                schema.kind,
+                RustSchemaKind::Tuple(_) | RustSchemaKind::TupleStruct(_)
+            )
+        })
+}
+
+/// Apply `f` to the children of the value, with their schema
+fn map_children<'a>(
+    kind: &'a RustSchemaKind,
+    value: Value,
+    f: impl Fn(&'a RustSchemaOrRef, Value) -> Value,
+) -> Value {
+    match (kind, value) {
+        (RustSchemaKind::Option(inner), Value::Option(Some(value))) => {
+            Value::Option(Some(Box::new(f(inner, *value))))
+        }
+        (RustSchemaKind::Array(array), Value::Array(values)) => match &array.template {
+            Some(template) => Value::Array(values.into_iter().map(|v| f(template, v)).collect()),
+            None => Value::Array(values),
+        },
+        (RustSchemaKind::Map(inner), Value::Map(map)) => {
+            Value::Map(map.0.into_iter().map(|(k, v)| (k, f(inner, v))).collect())
+        }
+        (RustSchemaKind::Tuple(fields), Value::Tuple(values)) => {
+            Value::Tuple(map_values(fields, values, f))
+        }
+        (RustSchemaKind::TupleStruct(tuple_struct), Value::Tuple(values)) => {
+            Value::Tuple(map_values(&tuple_struct.fields, values, f))
+        }
+        (RustSchemaKind::TupleStruct(tuple_struct), Value::TupleStruct(name, values)) => {
+            Value::TupleStruct(name, map_values(&tuple_struct.fields, values, f))
+        }
+        (RustSchemaKind::Struct(struct_), Value::Struct(name, map)) => Value::Struct(
+            name,
+            map_fields(|key| struct_.fields.get(key).map(|f| &f.schema), map, f),
+        ),
+        (_, value) => value,
+    }
+}
+
+fn map_values<'a>(
+    schemas: &'a [RustSchemaOrRef],
+    values: Vec<Value>,
+    f: impl Fn(&'a RustSchemaOrRef, Value) -> Value,
+) -> Vec<Value> {
+    values
+        .into_iter()
+        .enumerate()
+        .map(|(pos, value)| match schemas.get(pos) {
+            Some(schema) => f(schema, value),
+            None => value,
+        })
+        .collect()
+}
+
+/// Fields unknown to the schema are kept as they are
+fn map_fields<'a>(
+    schema_of: impl Fn(&str) -> Option<&'a RustSchemaOrRef>,
+    map: Map<String, Value>,
+    f: impl Fn(&'a RustSchemaOrRef, Value) -> Value,
+) -> Map<String, Value> {
+    map.0
+        .into_iter()
+        .map(|(key, value)| {
+            let value = match schema_of(&key) {
+                Some(schema) => f(schema, value),
+                None => value,
+            };
+            (key, value)
+        })
+        .collect()
+}
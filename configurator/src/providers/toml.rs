@@ -0,0 +1,242 @@
+use std::{collections::HashSet, fs, path::Path};
+
+use anyhow::{anyhow, bail};
+use rust_schema2::RustSchemaRoot;
+
+use crate::{
+    generic_value::Value,
+    providers::{Position, Provider, ReadError, atomic, json},
+};
+
+// TOML uses the same serde encoding as JSON for structs and enums,
+// so we go through a json value and reuse the conversions of the json provider.
+// TOML has no null: `None` is an absent key.
+
+#[derive(Debug)]
+pub struct TomlProvider;
+
+impl Provider for TomlProvider {
+    fn read(&self, path: &Path, root: &RustSchemaRoot) -> Result<Value, ReadError> {
+        read(path, root)
+    }
+
+    fn write(
+        &self,
+        path: &Path,
+        root: &RustSchemaRoot,
+        previous: &Value,
+        value: Option<Value>,
+        removed_unknown: &HashSet<String>,
+    ) -> anyhow::Result<()> {
+        write(path, root, value)
+    }
+}
+
+pub fn read(path: &Path, root: &RustSchemaRoot) -> Result<Value, ReadError> {
+    if !path.exists() {
+        return Ok(Value::Empty);
+    }
+
+    let content = fs::read_to_string(path).map_err(|e| ReadError::new(path, e))?;
+
+    let table: toml::Table = toml::from_str(&content).map_err(|e| {
+        let position = e
+            .span()
+            .map(|span| Position::from_byte_offset(&content, span.start));
+
+        let error = ReadError::new(path, anyhow!("{}", e.message()));
+
+        match position {
+            Some(position) => error.with_position(position),
+            None => error,
+        }
+    })?;
+
+    let schema = root
+        .resolve_schema(&root.schema)
+        .map_err(|e| ReadError::new(path, e))?;
+
+    let json_value =
+        toml_to_json(toml::Value::Table(table)).map_err(|e| ReadError::new(path, e))?;
+
+    json::json_to_value(root, Some(schema), json_value).map_err(|e| ReadError::new(path, e))
+}
+
+pub fn write(path: &Path, root: &RustSchemaRoot, value: Option<Value>) -> anyhow::Result<()> {
+    match value {
+        Some(value) => {
+            let schema = root.resolve_schema(&root.schema)?;
+
+            let json_value = json::value_to_json(root, Some(schema), value)?;
+
+            let Some(toml::Value::Table(table)) = json_to_toml(json_value)? else {
+                bail!("initial value is not a table")
+            };
+
+            let content = toml::to_string(&table)?;
+            atomic::write(path, content.as_bytes())?;
+        }
+        None => atomic::remove(path)?,
+    }
+
+    Ok(())
+}
+
+fn toml_to_json(value: toml::Value) -> anyhow::Result<::json::Value> {
+    let value = match value {
+        toml::Value::String(s) => ::json::Value::String(s),
+        toml::Value::Integer(i) => ::json::Value::Number(i.into()),
+        // not null, which would be read as `None`
+        toml::Value::Float(f) => ::json::Value::Number(
+            ::json::Number::from_f64(f).ok_or_else(|| anyhow!("{} is not supported", f))?,
+        ),
+        toml::Value::Boolean(b) => ::json::Value::Bool(b),
+        toml::Value::Datetime(datetime) => ::json::Value::String(datetime.to_string()),
+        toml::Value::Array(values) => ::json::Value::Array(
+            values
+                .into_iter()
+                .map(toml_to_json)
+                .collect::<anyhow::Result<_>>()?,
+        ),
+        toml::Value::Table(table) => ::json::Value::Object(
+            table
+                .into_iter()
+                .map(|(key, value)| Ok((key, toml_to_json(value)?)))
+                .collect::<anyhow::Result<_>>()?,
+        ),
+    };
+
+    Ok(value)
+}
+
+/// Return [`None`] for null
+fn json_to_toml(value: ::json::Value) -> anyhow::Result<Option<toml::Value>> {
+    let value = match value {
+        ::json::Value::Null => return Ok(None),
+        ::json::Value::Bool(b) => toml::Value::Boolean(b),
+        ::json::Value::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                toml::Value::Integer(i)
+            } else if n.is_f64()
+                && let Some(f) = n.as_f64()
+            {
+                toml::Value::Float(f)
+            } else {
+                bail!("{} is out of range for toml", n)
+            }
+        }
+        ::json::Value::String(s) => toml::Value::String(s),
+        ::json::Value::Array(values) => {
+            let mut array = toml::value::Array::with_capacity(values.len());
+
+            for value in values {
+                match json_to_toml(value)? {
+                    Some(value) => array.push(value),
+                    None => bail!("an array can't contain an empty value in toml"),
+                }
+            }
+
+            toml::Value::Array(array)
+        }
+        ::json::Value::Object(object) => {
+            let mut table = toml::Table::new();
+
+            for (key, value) in object {
+                if let Some(value) = json_to_toml(value)? {
+                    table.insert(key, value);
+                }
+            }
+
+            toml::Value::Table(table)
+        }
+    };
+
+    Ok(Some(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use rust_schema2::{RustSchema, schema_for};
+    use serde::{Deserialize, Serialize};
+
+    use super::*;
+    use crate::providers::tests::temp_dir;
+
+    #[derive(RustSchema, Serialize, Deserialize, Debug, PartialEq)]
+    enum Enum {
+        Unit,
+        Tuple(u8, bool),
+        Struct { c: char },
+    }
+
+    #[derive(RustSchema, Serialize, Deserialize, Debug, PartialEq)]
+    struct Inner {
+        a: i32,
+    }
+
+    #[derive(RustSchema, Serialize, Deserialize, Debug, PartialEq)]
+    struct Config {
+        none: Option<u32>,
+        some: Option<u32>,
+        float: f64,
+        inner: Inner,
+        enums: Vec<Enum>,
+    }
+
+    #[test]
+    fn roundtrip() {
+        let path = temp_dir();
+
+        let config = Config {
+            none: None,
+            some: Some(1),
+            float: 0.5,
+            inner: Inner { a: -1 },
+            enums: vec![Enum::Unit, Enum::Tuple(2, true), Enum::Struct { c: 'c' }],
+        };
+
+        fs::write(path.join("a.toml"), toml::to_string(&config).unwrap()).unwrap();
+
+        let root = schema_for::<Config>();
+        let value = read(&path.join("a.toml"), &root).unwrap();
+
+        let Value::Struct(_, fields) = &value else {
+            panic!("not a struct: {value:?}");
+        };
+        // an absent key
+        assert!(!fields.0.contains_key("none"));
+
+        write(&path.join("b.toml"), &root, Some(value)).unwrap();
+
+        let content = fs::read_to_string(path.join("b.toml")).unwrap();
+        assert_eq!(toml::from_str::<Config>(&content).unwrap(), config);
+
+        write(&path.join("b.toml"), &root, None).unwrap();
+        assert!(!path.join("b.toml").exists());
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+
+    #[test]
+    fn nan_and_inf() {
+        #[derive(RustSchema)]
+        #[allow(dead_code)]
+        struct Floats {
+            f: Option<f64>,
+        }
+
+        let path = temp_dir();
+        let root = schema_for::<Floats>();
+
+        for content in ["f = nan", "f = inf", "f = -inf"] {
+            fs::write(path.join("a.toml"), content).unwrap();
+
+            assert!(
+                read(&path.join("a.toml"), &root).is_err(),
+                "{content} was read"
+            );
+        }
+
+        fs::remove_dir_all(&path).unwrap();
+    }
+}
@@ -20,10 +20,12 @@ use crate::{
     icon, icon_button,
     message::{AppMsg, ChangeMsg, PageMsg},
     node::{
-        self, Node, NodeArray, NodeContainer, NodeString, NodeStruct,
+        self, Node, NodeArray, NodeBool, NodeChar, NodeContainer, NodeEnum, NodeMap, NodeNumber,
+        NodeOption, NodeString, NodeStruct, NodeTuple, NodeUnit, ValidationIssue,
         data_path::{self, DataPath, DataPathType, DataPathTypeCopy},
     },
-    page::Page,
+    page::{Conflict, Page, Resolution},
+    providers::{ReadError, cosmic_ron},
 };
 
 const SPACING: f32 = 10.;
@@ -42,7 +44,10 @@ pub fn view_app(app: &App) -> Element<'_, AppMsg> {
     }
 }
 
-fn view_data_path(data_path: &DataPath) -> Element<'_, PageMsg> {
+fn view_data_path<'a>(
+    data_path: &'a DataPath,
+    issues: &'a [ValidationIssue],
+) -> Element<'a, PageMsg> {
     let mut elements = Vec::new();
 
     let get_class = |pos: Option<usize>| {
@@ -59,6 +64,8 @@ fn view_data_path(data_path: &DataPath) -> Element<'_, PageMsg> {
             .class(get_class(None))
             .into(),
     );
+    // mark the components which contains an issue
+    elements.extend(issue_icon(issues, &[]));
 
     for (pos, component) in data_path.vec.iter().enumerate() {
         elements.push(
@@ -67,48 +74,143 @@ fn view_data_path(data_path: &DataPath) -> Element<'_, PageMsg> {
                 .class(get_class(Some(pos)))
                 .into(),
         );
+        elements.extend(issue_icon(issues, &data_path.vec[..=pos]));
     }
 
     row::with_children(elements).into()
 }
 
 fn view_page(entity: Entity, page: &Page) -> Element<'_, PageMsg> {
+    if !page.read_errors.is_empty() {
+        return view_read_errors(&page.read_errors);
+    }
+
     let data_path = page.data_path.current();
 
     let node = page.tree.get_at(Box::new(data_path.iter())).unwrap();
 
     let content = match &node.node {
-        // Node::Bool(node_bool) => view_bool(data_path, node, node_bool),
+        Node::Unit(node_unit) => view_unit(node, node_unit),
+        Node::Bool(node_bool) => view_bool(data_path, node, node_bool),
+        Node::Char(node_char) => view_char(data_path, node, node_char),
         Node::String(node_string) => view_string(data_path, node, node_string),
-        // Node::Number(node_number) => view_number(data_path, node, node_number),
+        Node::Number(node_number) => view_number(data_path, node, node_number),
         // Node::Object(node_object) => view_object(data_path, node, node_object),
-        // Node::Enum(node_enum) => view_enum(data_path, node, node_enum),
+        Node::Enum(node_enum) => view_enum(page, data_path, node, node_enum),
+        Node::Tuple(node_tuple) => view_tuple(page, data_path, node, node_tuple),
+        Node::Option(node_option) => view_option(page, data_path, node, node_option),
         // Node::Value(node_value) => view_value(data_path, node, node_value),
-        // Node::Unit => text("null").into(),
         // Node::Array(node_array) => view_array(data_path, node, node_array),
         Node::Struct(node_struct) => view_struct(page, data_path, node, node_struct),
         // Node::TupleStruct(_) => todo!(),
-        Node::Array(node_array) => view_array(data_path, node, node_array),
+        Node::Array(node_array) => view_array(page, data_path, node, node_array),
+        Node::Map(node_map) => view_map(page, data_path, node, node_map),
+        // built when opened
+        Node::Lazy(_) => text("Loading...").into(),
     };
 
     column()
-        .push(view_data_path(&page.data_path))
+        .push(view_data_path(&page.data_path, &page.issues))
+        .push_maybe(page.conflict.as_ref().map(view_conflict))
+        .push_maybe((!page.issues.is_empty()).then(|| {
+            section()
+                .title("Changes are not saved")
+                .extend(page.issues.iter().map(|issue| text(issue.to_string())))
+        }))
         .push(scrollable(content))
         .spacing(10)
         .into()
 }
 
-fn no_value_defined_warning_icon<'a, M: 'a>() -> Element<'a, M> {
+fn view_read_errors(read_errors: &[ReadError]) -> Element<'_, PageMsg> {
+    section()
+        .title("Some files can't be read")
+        .extend(read_errors.iter().enumerate().map(|(pos, read_error)| {
+            let message = match &read_error.position {
+                Some(position) => format!(
+                    "line {}, column {}: {}",
+                    position.line, position.column, read_error.error
+                ),
+                None => read_error.error.to_string(),
+            };
+
+            row()
+                .align_y(Alignment::Center)
+                .spacing(SPACING)
+                .push(
+                    column()
+                        .push(text(read_error.path.display().to_string()))
+                        .push(text::caption(message))
+                        .push_maybe(read_error.snippet.as_deref().map(text::monotext)),
+                )
+                .push(space::horizontal())
+                .push(button::text("ignore").on_press(PageMsg::IgnoreReadError(pos)))
+                .push(button::text("reset").on_press(PageMsg::ResetReadError(pos)))
+        }))
+        .into()
+}
+
+fn view_conflict(conflict: &Conflict) -> Element<'_, PageMsg> {
+    let value_to_string = |value: &Value| match value {
+        Value::Empty => "absent".to_string(),
+        value => unknown_value_to_string(value),
+    };
+
+    section()
+        .title("The config was changed by someone else")
+        .extend(conflict.diffs.iter().map(|diff| {
+            column()
+                .push(text(diff.path.join(".")))
+                .push(text::caption(format!(
+                    "before: {}, mine: {}, theirs: {}",
+                    value_to_string(&diff.base),
+                    value_to_string(&diff.mine),
+                    value_to_string(&diff.theirs)
+                )))
+        }))
+        .add(
+            row()
+                .spacing(SPACING)
+                .push(space::horizontal())
+                .push(button::text("merge").on_press(PageMsg::ResolveConflict(Resolution::Merge)))
+                .push(
+                    button::text("keep mine")
+                        .on_press(PageMsg::ResolveConflict(Resolution::KeepMine)),
+                )
+                .push(
+                    button::text("take theirs")
+                        .on_press(PageMsg::ResolveConflict(Resolution::TakeTheirs)),
+                ),
+        )
+        .into()
+}
+
+fn warning_icon<'a, M: 'a>(message: String) -> Element<'a, M> {
     tooltip(
         icon!("report24").class(cosmic::theme::Svg::custom(|e| cosmic::widget::svg::Style {
             color: Some(Color::from_rgb(236.0 / 255.0, 194.0 / 255.0, 58.0 / 255.0)),
         })),
-        text("You need to define some values that have no default!"),
+        text(message),
         Position::Top,
     )
     .into()
 }
 
+/// Warn about the first issue at `data_path` or in one of its children
+fn issue_icon<'a, M: 'a>(
+    issues: &[ValidationIssue],
+    data_path: &[DataPathType],
+) -> Option<Element<'a, M>> {
+    issues
+        .iter()
+        .find(|issue| issue.is_under(data_path))
+        .map(|issue| warning_icon(issue.to_string()))
+}
+
+fn no_value_defined_warning_icon<'a, M: 'a>() -> Element<'a, M> {
+    warning_icon("You need to define some values that have no default!".to_string())
+}
+
 fn this_will_remove_all_children<'a, M: 'a>() -> Element<'a, M> {
     tooltip(
         icon!("info24"),
@@ -119,6 +221,7 @@ fn this_will_remove_all_children<'a, M: 'a>() -> Element<'a, M> {
 }
 
 fn node_list<'a>(
+    page: &'a Page,
     name: DataPathTypeCopy<'a>,
     data_path: &'a [DataPathType],
     node: &'a NodeContainer,
@@ -131,22 +234,23 @@ fn node_list<'a>(
                     .push(text(format!("{}", name)))
                     .push_maybe(node.description.as_ref().map(text::caption)),
             )
-            // .push_maybe(
-            //     if inner_node.removable
-            //         && let DataPathType::Name(name) = &name
-            //     {
-            //         Some(
-            //             button::text("edit key")
-            //                 .on_press(PageMsg::DialogRenameKey(data_path.to_vec(), name.clone())),
-            //         )
-            //     } else {
-            //         None
-            //     },
-            // )
+            .push_maybe(
+                // only map entries have a removable key
+                if node.is_removable
+                    && let DataPathTypeCopy::Name(key) = name
+                {
+                    Some(
+                        button::text("edit key")
+                            .on_press(PageMsg::DialogRenameKey(data_path.to_vec(), key.clone())),
+                    )
+                } else {
+                    None
+                },
+            )
             .push(space::horizontal())
             .push_maybe(match &node.node {
-                // Node::Unit => Some(Element::from(text("null"))),
-                Node::String(node_string) => Some({
+                Node::Unit(node_unit) => Some(Element::from(text(unit_marker(node_unit)))),
+                Node::String(node_string) => Some(Element::from(
                     text_input("value", node_string.value.as_ref().map_or("", |v| v)).on_input(
                         move |value| {
                             PageMsg::ChangeMsg(
@@ -154,73 +258,67 @@ fn node_list<'a>(
                                 ChangeMsg::ChangeString(value),
                             )
                         },
-                    )
-                }),
-
-                // Node::Bool(node_bool) => Some(
-                //     toggler(node_bool.value.unwrap_or_default())
-                //         .on_toggle(move |value| {
-                //             PageMsg::ChangeMsg(
-                //                 append_data_path(data_path, &name),
-                //                 ChangeMsg::ChangeBool(value),
-                //             )
-                //         })
-                //         .into(),
-                // ),
-                // Node::Enum(node_enum) => {
-                //     #[derive(Eq, Clone)]
-                //     struct Key<'a> {
-                //         pub pos: usize,
-                //         pub value: Cow<'a, str>,
-                //     }
-                //     impl PartialEq for Key<'_> {
-                //         fn eq(&self, other: &Self) -> bool {
-                //             self.pos == other.pos
-                //         }
-                //     }
-                //     #[allow(clippy::to_string_trait_impl)]
-                //     impl ToString for Key<'_> {
-                //         fn to_string(&self) -> String {
-                //             self.value.to_string()
-                //         }
-                //     }
-                //     Some(
-                //         row()
-                //             .push_maybe(node_enum.value.map(|pos| {
-                //                 text(
-                //                     node_to_str(&node_enum.nodes[pos])
-                //                         .unwrap_or(Cow::Owned(pos.to_string())),
-                //                 )
-                //             }))
-                //             .push(pick_list(
-                //                 node_enum
-                //                     .nodes
-                //                     .iter()
-                //                     .enumerate()
-                //                     .map(|(pos, node)| Key {
-                //                         pos,
-                //                         value: node_to_str(node)
-                //                             .unwrap_or(Cow::Owned(pos.to_string())),
-                //                     })
-                //                     .collect::<Vec<_>>(),
-                //                 node_enum.value.map(|pos| Key {
-                //                     pos,
-                //                     value: Cow::Borrowed(""),
-                //                 }),
-                //                 move |key| {
-                //                     PageMsg::ChangeMsg(
-                //                         append_data_path(data_path, &name),
-                //                         ChangeMsg::ChangeEnum(key.pos),
-                //                     )
-                //                 },
-                //             ))
-                //             .align_y(alignment::Vertical::Center)
-                //             .into(),
-                //     )
-                // }
+                    ),
+                )),
+
+                Node::Bool(node_bool) => Some(
+                    toggler(node_bool.value.unwrap_or_default())
+                        .on_toggle(move |value| {
+                            PageMsg::ChangeMsg(
+                                data_path::push_one(data_path, name),
+                                ChangeMsg::ChangeBool(value),
+                            )
+                        })
+                        .into(),
+                ),
+                Node::Number(node_number) => Some(
+                    row()
+                        .push(text_input("value", &node_number.value_string).on_input(
+                            move |value| {
+                                PageMsg::ChangeMsg(
+                                    data_path::push_one(data_path, name),
+                                    ChangeMsg::ChangeNumber(value),
+                                )
+                            },
+                        ))
+                        .push_maybe(number_error_icon(node_number))
+                        .align_y(Alignment::Center)
+                        .into(),
+                ),
+                Node::Char(node_char) => Some(
+                    row()
+                        .push(
+                            text_input("value", &node_char.value_string).on_input(move |value| {
+                                PageMsg::ChangeMsg(
+                                    data_path::push_one(data_path, name),
+                                    ChangeMsg::ChangeChar(value),
+                                )
+                            }),
+                        )
+                        .push_maybe(char_error_icon(node_char))
+                        .align_y(Alignment::Center)
+                        .into(),
+                ),
+                Node::Enum(node_enum) => Some(variant_pick_list(
+                    data_path::push_one(data_path, name),
+                    node_enum,
+                )),
+                Node::Option(node_option) => Some(
+                    toggler(node_option.is_some.unwrap_or_default())
+                        .on_toggle(move |value| {
+                            PageMsg::ChangeMsg(
+                                data_path::push_one(data_path, name),
+                                ChangeMsg::ChangeOption(value),
+                            )
+                        })
+                        .into(),
+                ),
                 _ => None,
             })
-            .push_maybe((!node.is_valid()).then(|| no_value_defined_warning_icon()))
+            .push_maybe(issue_icon(
+                &page.issues,
+                &data_path::push_one(data_path, name),
+            ))
             .push_maybe(node.is_removable.then(|| {
                 icon_button!("close24").on_press(PageMsg::ChangeMsg(
                     data_path.to_vec(),
@@ -293,14 +391,29 @@ fn view_struct<'a>(
                 .as_ref()
                 .map(|desc| section().title("Description").add(text(desc))),
         )
-        .push(
-            section().title("Values").extend(
-                node_struct
-                    .fields
-                    .iter()
-                    .map(|(name, field)| node_list(DataPathTypeCopy::Name(name), data_path, field)),
-            ),
-        )
+        .push(section().title("Values").extend(
+            node_struct.fields.iter().map(|(name, field)| {
+                node_list(page, DataPathTypeCopy::Name(name), data_path, field)
+            }),
+        ))
+        .push_maybe((!node_struct.unknown.is_empty()).then(|| {
+            section()
+                .title("Unrecognized")
+                .extend(node_struct.unknown.iter().map(|(name, value)| {
+                    row()
+                        .align_y(Alignment::Center)
+                        .push(
+                            column()
+                                .push(text(name))
+                                .push(text::caption(unknown_value_to_string(value))),
+                        )
+                        .push(space::horizontal())
+                        .push(button::text("remove").on_press(PageMsg::ChangeMsg(
+                            data_path.to_vec(),
+                            ChangeMsg::RemoveUnknown(name.clone()),
+                        )))
+                }))
+        }))
         .push_maybe(node.default.is_not_empty().then(|| {
             section().title("Default").add(
                 row()
@@ -317,7 +430,15 @@ fn view_struct<'a>(
         .into()
 }
 
+/// The schema doesn't describe the value, so we show it as ron
+fn unknown_value_to_string(value: &Value) -> String {
+    let value = cosmic_ron::value_to_ron_value(value.clone());
+
+    ron_value::to_string(&value).unwrap_or_else(|e| e.to_string())
+}
+
 fn view_array<'a>(
+    page: &'a Page,
     data_path: &'a [DataPathType],
     node: &'a NodeContainer,
     node_array: &'a NodeArray,
@@ -337,7 +458,7 @@ fn view_array<'a>(
                     .iter()
                     .enumerate()
                     .map(|(pos, inner_node)| {
-                        node_list(DataPathTypeCopy::Indice(pos), data_path, inner_node)
+                        node_list(page, DataPathTypeCopy::Indice(pos), data_path, inner_node)
                     }),
             ),
         )
@@ -377,141 +498,306 @@ fn view_array<'a>(
         .into()
 }
 
-// fn view_enum<'a>(
-//     data_path: &'a [DataPathType],
-//     node: &'a NodeContainer,
-//     node_enum: &'a NodeEnum,
-// ) -> Element<'a, PageMsg> {
-//     column()
-//         .push_maybe(
-//             node.description
-//                 .as_ref()
-//                 .map(|desc| section().title("Description").add(text(desc))),
-//         )
-//         .push(
-//             section()
-//                 .title("Values")
-//                 .extend(node_enum.nodes.iter().enumerate().map(|(pos, inner_node)| {
-//                     container(cosmic::widget::radio(
-//                         {
-//                             let is_active = if let Some(active_pos) = node_enum.value
-//                                 && active_pos == pos
-//                             {
-//                                 Some(())
-//                             } else {
-//                                 None
-//                             };
-
-//                             row()
-//                                 .push(text(
-//                                     node_to_str(inner_node).unwrap_or(Cow::Owned(pos.to_string())),
-//                                 ))
-//                                 .push(horizontal_space())
-//                                 .push_maybe(is_active.map(|_| {
-//                                     button::text("modify")
-//                                         .on_press(PageMsg::OpenDataPath(DataPathType::Indice(pos)))
-//                                 }))
-//                                 .push_maybe(is_active.and_then(|_| {
-//                                     if !inner_node.is_valid() {
-//                                         Some(no_value_defined_warning_icon())
-//                                     } else {
-//                                         None
-//                                     }
-//                                 }))
-//                                 .align_y(Alignment::Center)
-//                         },
-//                         pos,
-//                         node_enum.value,
-//                         |pos| PageMsg::ChangeMsg(data_path.to_vec(), ChangeMsg::ChangeEnum(pos)),
-//                     ))
-//                     .padding(5)
-//                 })),
-//         )
-//         .push_maybe(node.default.as_ref().map(|default| {
-//             section()
-//                 .title("Default")
-//                 .add(
-//                     container(
-//                         row()
-//                             .push(text("Default value"))
-//                             .push(horizontal_space())
-//                             .push(text(
-//                                 value_to_str(default)
-//                                     .unwrap_or(Cow::Borrowed("Value not displayable")),
-//                             )),
-//                     )
-//                     .padding(10),
-//                 )
-//                 .add(
-//                     row()
-//                         .push(horizontal_space())
-//                         .push(
-//                             // xxx: the on_press need to be lazy
-//                             button::text("reset to default").on_press(PageMsg::ChangeMsg(
-//                                 data_path.to_vec(),
-//                                 ChangeMsg::ApplyDefault,
-//                             )),
-//                         )
-//                         .push(this_will_remove_all_children()),
-//                 )
-//         }))
-//         .spacing(SPACING)
-//         .into()
-// }
+fn view_map<'a>(
+    page: &'a Page,
+    data_path: &'a [DataPathType],
+    node: &'a NodeContainer,
+    node_map: &'a NodeMap,
+) -> Element<'a, PageMsg> {
+    column()
+        .push_maybe(
+            node.description
+                .as_ref()
+                .map(|desc| section().title("Description").add(text(desc))),
+        )
+        .push(
+            section()
+                .title("Values")
+                .extend(node_map.value.iter().flatten().map(|(key, inner_node)| {
+                    node_list(page, DataPathTypeCopy::Name(key), data_path, inner_node)
+                })),
+        )
+        .push(icon_button!("add24").on_press(PageMsg::DialogAddNewNodeToObject(data_path.to_vec())))
+        .push_maybe(node.default.is_not_empty().then(|| {
+            section().title("Default").add(
+                row()
+                    .push(space::horizontal())
+                    .push(
+                        // xxx: the on_press need to be lazy
+                        button::text("reset to default")
+                            .on_press(PageMsg::ApplyDefault(data_path.to_vec())),
+                    )
+                    .push(this_will_remove_all_children()),
+            )
+        }))
+        .spacing(SPACING)
+        .into()
+}
 
-// fn view_bool<'a>(
-//     data_path: &'a [DataPathType],
-//     node: &'a NodeContainer,
-//     node_bool: &'a NodeBool,
-// ) -> Element<'a, PageMsg> {
-//     column()
-//         .push_maybe(
-//             node.description
-//                 .as_ref()
-//                 .map(|desc| section().title("Description").add(text(desc))),
-//         )
-//         .push(
-//             section().title("Value").add(
-//                 row()
-//                     .push(text("Current value"))
-//                     .push(horizontal_space())
-//                     .push(
-//                         toggler(node_bool.value.unwrap_or_default()).on_toggle(move |value| {
-//                             PageMsg::ChangeMsg(data_path.to_vec(), ChangeMsg::ChangeBool(value))
-//                         }),
-//                     )
-//                     .push_maybe(if node_bool.value.is_none() {
-//                         Some(no_value_defined_warning_icon())
-//                     } else {
-//                         None
-//                     }),
-//             ),
-//         )
-//         .push_maybe(
-//             node.default
-//                 .as_ref()
-//                 .and_then(|v| v.as_bool())
-//                 .map(|default| {
-//                     section()
-//                         .title("Default")
-//                         .add(
-//                             row()
-//                                 .push(text("Default value"))
-//                                 .push(horizontal_space())
-//                                 .push(toggler(*default)),
-//                         )
-//                         .add(row().push(horizontal_space()).push(
-//                             // xxx: the on_press need to be lazy
-//                             button::text("reset to default").on_press(PageMsg::ChangeMsg(
-//                                 data_path.to_vec(),
-//                                 ChangeMsg::ApplyDefault,
-//                             )),
-//                         ))
-//                 }),
-//         )
-//         .spacing(SPACING)
-//         .into()
-// }
+#[derive(Clone, Eq)]
+struct VariantKey<'a> {
+    pos: usize,
+    name: &'a str,
+}
+
+impl PartialEq for VariantKey<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.pos == other.pos
+    }
+}
+
+impl Display for VariantKey<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+fn variant_pick_list<'a>(
+    data_path: Vec<DataPathType>,
+    node_enum: &'a NodeEnum,
+) -> Element<'a, PageMsg> {
+    pick_list(
+        node_enum
+            .variants
+            .iter()
+            .enumerate()
+            .map(|(pos, variant)| VariantKey {
+                pos,
+                name: &variant.name,
+            })
+            .collect::<Vec<_>>(),
+        node_enum.value.map(|pos| VariantKey {
+            pos,
+            name: &node_enum.variants[pos].name,
+        }),
+        move |key| PageMsg::ChangeMsg(data_path.clone(), ChangeMsg::ChangeEnum(key.pos)),
+    )
+    .into()
+}
+
+fn view_enum<'a>(
+    page: &'a Page,
+    data_path: &'a [DataPathType],
+    node: &'a NodeContainer,
+    node_enum: &'a NodeEnum,
+) -> Element<'a, PageMsg> {
+    let active = node_enum.value.map(|pos| (pos, &node_enum.variants[pos]));
+
+    column()
+        .push_maybe(
+            node.description
+                .as_ref()
+                .map(|desc| section().title("Description").add(text(desc))),
+        )
+        .push(
+            section().title("Value").add(
+                row()
+                    .push(text("Variant"))
+                    .push(space::horizontal())
+                    .push(variant_pick_list(data_path.to_vec(), node_enum))
+                    .push_maybe(if node_enum.value.is_none() {
+                        Some(no_value_defined_warning_icon())
+                    } else {
+                        None
+                    })
+                    .align_y(Alignment::Center),
+            ),
+        )
+        .push_maybe(active.and_then(|(_, variant)| {
+            variant
+                .description
+                .as_ref()
+                .map(|desc| section().title("Variant description").add(text(desc)))
+        }))
+        .push_maybe(active.and_then(|(pos, variant)| {
+            variant.node.is_some().then(|| {
+                section().title("Payload").add(
+                    mouse_area(
+                        row()
+                            .align_y(Alignment::Center)
+                            .push(text(&variant.name))
+                            .push(space::horizontal())
+                            .push_maybe(issue_icon(
+                                &page.issues,
+                                &data_path::push_one(data_path, DataPathType::Indice(pos)),
+                            )),
+                    )
+                    .on_press(PageMsg::OpenDataPath(DataPathType::Indice(pos))),
+                )
+            })
+        }))
+        .push_maybe(node.default.enum_variant_name().map(|default| {
+            section()
+                .title("Default")
+                .add(
+                    row()
+                        .push(text("Default value"))
+                        .push(space::horizontal())
+                        .push(text(default)),
+                )
+                .add(
+                    row()
+                        .push(space::horizontal())
+                        .push(
+                            // xxx: the on_press need to be lazy
+                            button::text("reset to default")
+                                .on_press(PageMsg::ApplyDefault(data_path.to_vec())),
+                        )
+                        .push(this_will_remove_all_children()),
+                )
+        }))
+        .spacing(SPACING)
+        .into()
+}
+
+fn view_tuple<'a>(
+    page: &'a Page,
+    data_path: &'a [DataPathType],
+    node: &'a NodeContainer,
+    node_tuple: &'a NodeTuple,
+) -> Element<'a, PageMsg> {
+    column()
+        .push_maybe(
+            node.name
+                .as_ref()
+                .map(|name| section().title("Name").add(text(name))),
+        )
+        .push_maybe(
+            node.description
+                .as_ref()
+                .map(|desc| section().title("Description").add(text(desc))),
+        )
+        .push(section().title("Values").extend(
+            node_tuple.fields.iter().enumerate().map(|(pos, field)| {
+                node_list(page, DataPathTypeCopy::Indice(pos), data_path, field)
+            }),
+        ))
+        .push_maybe(node.default.is_not_empty().then(|| {
+            section().title("Default").add(
+                row()
+                    .push(space::horizontal())
+                    .push(
+                        // xxx: the on_press need to be lazy
+                        button::text("reset to default")
+                            .on_press(PageMsg::ApplyDefault(data_path.to_vec())),
+                    )
+                    .push(this_will_remove_all_children()),
+            )
+        }))
+        .spacing(SPACING)
+        .into()
+}
+
+fn view_option<'a>(
+    page: &'a Page,
+    data_path: &'a [DataPathType],
+    node: &'a NodeContainer,
+    node_option: &'a NodeOption,
+) -> Element<'a, PageMsg> {
+    column()
+        .push_maybe(
+            node.description
+                .as_ref()
+                .map(|desc| section().title("Description").add(text(desc))),
+        )
+        .push(
+            section().title("Value").add(
+                row()
+                    .push(text("Some"))
+                    .push(space::horizontal())
+                    .push(toggler(node_option.is_some.unwrap_or_default()).on_toggle(
+                        move |value| {
+                            PageMsg::ChangeMsg(data_path.to_vec(), ChangeMsg::ChangeOption(value))
+                        },
+                    ))
+                    .push_maybe(if node_option.is_some.is_none() {
+                        Some(no_value_defined_warning_icon())
+                    } else {
+                        None
+                    })
+                    .align_y(Alignment::Center),
+            ),
+        )
+        .push_maybe(node_option.some_node().map(|inner_node| {
+            section().title("Some value").add(node_list(
+                page,
+                DataPathTypeCopy::Indice(0),
+                data_path,
+                inner_node,
+            ))
+        }))
+        .push_maybe(node.default.as_implicit_option().map(|default| {
+            section()
+                .title("Default")
+                .add(
+                    row()
+                        .push(text("Default value"))
+                        .push(space::horizontal())
+                        .push(text(if default.is_some() { "Some" } else { "None" })),
+                )
+                .add(
+                    row()
+                        .push(space::horizontal())
+                        .push(
+                            // xxx: the on_press need to be lazy
+                            button::text("reset to default")
+                                .on_press(PageMsg::ApplyDefault(data_path.to_vec())),
+                        )
+                        .push(this_will_remove_all_children()),
+                )
+        }))
+        .spacing(SPACING)
+        .into()
+}
+
+fn view_bool<'a>(
+    data_path: &'a [DataPathType],
+    node: &'a NodeContainer,
+    node_bool: &'a NodeBool,
+) -> Element<'a, PageMsg> {
+    column()
+        .push_maybe(
+            node.description
+                .as_ref()
+                .map(|desc| section().title("Description").add(text(desc))),
+        )
+        .push(
+            section().title("Value").add(
+                row()
+                    .push(text("Current value"))
+                    .push(space::horizontal())
+                    .push(
+                        toggler(node_bool.value.unwrap_or_default()).on_toggle(move |value| {
+                            PageMsg::ChangeMsg(data_path.to_vec(), ChangeMsg::ChangeBool(value))
+                        }),
+                    )
+                    .push_maybe(if node_bool.value.is_none() {
+                        Some(no_value_defined_warning_icon())
+                    } else {
+                        None
+                    }),
+            ),
+        )
+        .push_maybe(node.default.as_bool().map(|default| {
+            section()
+                .title("Default")
+                .add(
+                    row()
+                        .push(text("Default value"))
+                        .push(space::horizontal())
+                        .push(toggler(*default)),
+                )
+                .add(
+                    row().push(space::horizontal()).push(
+                        // xxx: the on_press need to be lazy
+                        button::text("reset to default")
+                            .on_press(PageMsg::ApplyDefault(data_path.to_vec())),
+                    ),
+                )
+        }))
+        .spacing(SPACING)
+        .into()
+}
 
 fn view_string<'a>(
     data_path: &'a [DataPathType],
@@ -562,68 +848,150 @@ fn view_string<'a>(
         .into()
 }
 
-// fn view_number<'a>(
-//     data_path: &'a [DataPathType],
-//     node: &'a NodeContainer,
-//     node_number: &'a NodeNumber,
-// ) -> Element<'a, PageMsg> {
-//     column()
-//         .push_maybe(
-//             node.description
-//                 .as_ref()
-//                 .map(|desc| section().title("Description").add(text(desc))),
-//         )
-//         .push(
-//             section().title("Value").add(
-//                 row()
-//                     .push(text("Current value"))
-//                     .push(horizontal_space())
-//                     .push(
-//                         text_input("value", &node_number.temp).on_input(move |value| {
-//                             PageMsg::ChangeMsg(data_path.to_vec(), ChangeMsg::ChangeNumber(value))
-//                         }),
-//                     )
-//                     .push_maybe(if node_number.value.is_none() {
-//                         Some(no_value_defined_warning_icon())
-//                     } else if node_number.try_parse_from_str(&node_number.temp).is_err() {
-//                         Some(
-//                             tooltip(
-//                                 icon!("report24"),
-//                                 text("This value is incorrect."),
-//                                 Position::Top,
-//                             )
-//                             .into(),
-//                         )
-//                     } else {
-//                         None
-//                     }),
-//             ),
-//         )
-//         .push_maybe(
-//             node.default
-//                 .as_ref()
-//                 .and_then(|v| v.as_number())
-//                 .map(|default| {
-//                     section()
-//                         .title("Default")
-//                         .add(
-//                             row()
-//                                 .push(text("Default value"))
-//                                 .push(horizontal_space())
-//                                 .push(text(NumberValue::from_number(default).to_string())),
-//                         )
-//                         .add(row().push(horizontal_space()).push(
-//                             // xxx: the on_press need to be lazy
-//                             button::text("reset to default").on_press(PageMsg::ChangeMsg(
-//                                 data_path.to_vec(),
-//                                 ChangeMsg::ApplyDefault,
-//                             )),
-//                         ))
-//                 }),
-//         )
-//         .spacing(SPACING)
-//         .into()
-// }
+fn number_error_icon<'a, M: 'a>(node_number: &NodeNumber) -> Option<Element<'a, M>> {
+    if node_number.value_string.is_empty() {
+        return None;
+    }
+
+    node_number
+        .try_parse_from_str(&node_number.value_string)
+        .err()
+        .map(|err| tooltip(icon!("report24"), text(err.to_string()), Position::Top).into())
+}
+
+fn view_number<'a>(
+    data_path: &'a [DataPathType],
+    node: &'a NodeContainer,
+    node_number: &'a NodeNumber,
+) -> Element<'a, PageMsg> {
+    column()
+        .push_maybe(
+            node.description
+                .as_ref()
+                .map(|desc| section().title("Description").add(text(desc))),
+        )
+        .push(
+            section().title("Value").add(
+                row()
+                    .push(text("Current value"))
+                    .push(space::horizontal())
+                    .push(
+                        text_input("value", &node_number.value_string).on_input(move |value| {
+                            PageMsg::ChangeMsg(data_path.to_vec(), ChangeMsg::ChangeNumber(value))
+                        }),
+                    )
+                    .push_maybe(number_error_icon(node_number).or_else(|| {
+                        node_number
+                            .value
+                            .is_none()
+                            .then(no_value_defined_warning_icon)
+                    })),
+            ),
+        )
+        .push_maybe(node.default.as_number().map(|default| {
+            section()
+                .title("Default")
+                .add(
+                    row()
+                        .push(text("Default value"))
+                        .push(space::horizontal())
+                        .push(text(default.to_string())),
+                )
+                .add(
+                    row().push(space::horizontal()).push(
+                        // xxx: the on_press need to be lazy
+                        button::text("reset to default")
+                            .on_press(PageMsg::ApplyDefault(data_path.to_vec())),
+                    ),
+                )
+        }))
+        .spacing(SPACING)
+        .into()
+}
+
+fn unit_marker(node_unit: &NodeUnit) -> &str {
+    node_unit.name.as_deref().unwrap_or("()")
+}
+
+fn view_unit<'a>(node: &'a NodeContainer, node_unit: &'a NodeUnit) -> Element<'a, PageMsg> {
+    column()
+        .push_maybe(
+            node.description
+                .as_ref()
+                .map(|desc| section().title("Description").add(text(desc))),
+        )
+        .push(
+            section().title("Value").add(
+                row()
+                    .push(text("This value can't be changed"))
+                    .push(space::horizontal())
+                    .push(text(unit_marker(node_unit))),
+            ),
+        )
+        .spacing(SPACING)
+        .into()
+}
+
+fn char_error_icon<'a, M: 'a>(node_char: &NodeChar) -> Option<Element<'a, M>> {
+    if node_char.value_string.is_empty() {
+        return None;
+    }
+
+    node_char
+        .try_parse_from_str(&node_char.value_string)
+        .err()
+        .map(|err| tooltip(icon!("report24"), text(err.to_string()), Position::Top).into())
+}
+
+fn view_char<'a>(
+    data_path: &'a [DataPathType],
+    node: &'a NodeContainer,
+    node_char: &'a NodeChar,
+) -> Element<'a, PageMsg> {
+    column()
+        .push_maybe(
+            node.description
+                .as_ref()
+                .map(|desc| section().title("Description").add(text(desc))),
+        )
+        .push(
+            section().title("Value").add(
+                row()
+                    .push(text("Current value"))
+                    .push(space::horizontal())
+                    .push(
+                        text_input("value", &node_char.value_string).on_input(move |value| {
+                            PageMsg::ChangeMsg(data_path.to_vec(), ChangeMsg::ChangeChar(value))
+                        }),
+                    )
+                    .push_maybe(char_error_icon(node_char).or_else(|| {
+                        node_char
+                            .value
+                            .is_none()
+                            .then(no_value_defined_warning_icon)
+                    })),
+            ),
+        )
+        .push_maybe(node.default.as_char().map(|default| {
+            section()
+                .title("Default")
+                .add(
+                    row()
+                        .push(text("Default value"))
+                        .push(space::horizontal())
+                        .push(text(default.to_string())),
+                )
+                .add(
+                    row().push(space::horizontal()).push(
+                        // xxx: the on_press need to be lazy
+                        button::text("reset to default")
+                            .on_press(PageMsg::ApplyDefault(data_path.to_vec())),
+                    ),
+                )
+        }))
+        .spacing(SPACING)
+        .into()
+}
 
 // fn view_value<'a>(
 //     data_path: &'a [DataPathType],
@@ -669,7 +1037,7 @@ fn value_to_str(value: &Value) -> Option<Cow<'_, str>> {
             true => Some(Cow::Borrowed("true")),
             false => Some(Cow::Borrowed("false")),
         },
-        Value::Char(_) => todo!(),
+        Value::Char(c) => Some(Cow::Owned(c.to_string())),
         Value::Number(number) => Some(Cow::Owned(number.to_string())),
         Value::String(s) => Some(Cow::Borrowed(s)),
         Value::Bytes(items) => todo!(),
@@ -0,0 +1,262 @@
+use std::path::{Path, PathBuf};
+
+use cosmic::iced::{
+    Subscription,
+    futures::{SinkExt, StreamExt, channel::mpsc},
+    stream,
+};
+use notify::{RecursiveMode, Watcher};
+
+use crate::providers::Layout;
+
+/// A config path, and how the config is laid out there
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct WatchedPath {
+    pub path: PathBuf,
+    pub layout: Layout,
+}
+
+impl WatchedPath {
+    pub fn contains(&self, path: &Path) -> bool {
+        path.starts_with(&self.path)
+    }
+
+    /// The directory to watch for this config.
+    ///
+    /// Files are replaced when written, by editors and by us, so we watch
+    /// the directory which contains them. The config can be created later:
+    /// until then, we watch the first directory which exists, to at least
+    /// see the creation of its direct children.
+    fn target(&self) -> Option<WatchTarget> {
+        let (dir, recursive) = match self.layout {
+            Layout::File => (self.path.parent()?, false),
+            Layout::Directory => (self.path.as_path(), true),
+        };
+
+        let existing = dir.ancestors().find(|dir| dir.exists())?;
+
+        Some(WatchTarget {
+            dir: existing.to_path_buf(),
+            recursive: recursive && existing == dir,
+            complete: existing == dir,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct WatchTarget {
+    dir: PathBuf,
+    recursive: bool,
+    /// The directory of the config exists, so we see all its changes
+    complete: bool,
+}
+
+impl WatchTarget {
+    fn mode(&self) -> RecursiveMode {
+        if self.recursive {
+            RecursiveMode::Recursive
+        } else {
+            RecursiveMode::NonRecursive
+        }
+    }
+}
+
+/// Emit the paths changed on disk under `paths`, including the ones we wrote.
+pub fn subscription(paths: Vec<WatchedPath>) -> Subscription<PathBuf> {
+    Subscription::run_with(paths, |paths| {
+        let paths = paths.clone();
+
+        stream::channel(100, move |mut output| async move {
+            let (tx, mut rx) = mpsc::unbounded();
+
+            let watcher =
+                notify::recommended_watcher(move |res: notify::Result<notify::Event>| match res {
+                    Ok(event) if !event.kind.is_access() => {
+                        for path in event.paths {
+                            let _ = tx.unbounded_send(path);
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(e) => error!("{e}"),
+                });
+
+            let mut watcher = match watcher {
+                Ok(watcher) => watcher,
+                Err(e) => {
+                    error!("can't watch the config files: {e}");
+                    return;
+                }
+            };
+
+            let mut targets = vec![None; paths.len()];
+            update_watches(&mut watcher, &paths, &mut targets);
+
+            while let Some(path) = rx.next().await {
+                // the directory of a config could have been created or removed
+                for watched_path in update_watches(&mut watcher, &paths, &mut targets) {
+                    // its files could have been written before we watched it
+                    let _ = output.send(watched_path.path.clone()).await;
+                }
+
+                // temporary files of our writes
+                if path
+                    .file_name()
+                    .and_then(|filename| filename.to_str())
+                    .is_some_and(|filename| filename.starts_with('.'))
+                {
+                    continue;
+                }
+
+                if paths
+                    .iter()
+                    .any(|watched_path| watched_path.contains(&path))
+                {
+                    let _ = output.send(path).await;
+                }
+            }
+        })
+    })
+}
+
+/// Watch the current target of each path. `targets` are the directories
+/// watched for `paths`, by position. Return the paths whose directory
+/// just started to be watched.
+fn update_watches<'a>(
+    watcher: &mut impl Watcher,
+    paths: &'a [WatchedPath],
+    targets: &mut [Option<WatchTarget>],
+) -> Vec<&'a WatchedPath> {
+    let mut completed = Vec::new();
+
+    // a directory can be created while we add a watch on its parent
+    loop {
+        let mut changed = false;
+
+        for (pos, watched_path) in paths.iter().enumerate() {
+            let target = watched_path.target();
+
+            if target == targets[pos] {
+                continue;
+            }
+
+            changed = true;
+
+            if let Some(old) = targets[pos].take()
+                && !targets.iter().flatten().any(|other| other.dir == old.dir)
+                && let Err(e) = watcher.unwatch(&old.dir)
+            {
+                debug!("can't unwatch {}: {e}", old.dir.display());
+            }
+
+            if let Some(target) = &target {
+                match watcher.watch(&target.dir, target.mode()) {
+                    Ok(()) if target.complete => completed.push(watched_path),
+                    Ok(()) => {}
+                    // not retried until the target changes
+                    Err(e) => warn!("can't watch {}: {e}", target.dir.display()),
+                }
+            }
+
+            targets[pos] = target;
+        }
+
+        if !changed {
+            return completed;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, sync::mpsc, time::Duration};
+
+    use super::*;
+    use crate::providers::tests::temp_dir;
+
+    #[test]
+    fn target_of_missing_dir() {
+        let dir = temp_dir();
+
+        let file = WatchedPath {
+            path: dir.join("a/b/config.toml"),
+            layout: Layout::File,
+        };
+        let directory = WatchedPath {
+            path: dir.join("a/c"),
+            layout: Layout::Directory,
+        };
+
+        for watched_path in [&file, &directory] {
+            assert_eq!(
+                watched_path.target(),
+                Some(WatchTarget {
+                    dir: dir.clone(),
+                    recursive: false,
+                    complete: false,
+                })
+            );
+        }
+
+        fs::create_dir_all(dir.join("a/b")).unwrap();
+        fs::create_dir_all(dir.join("a/c")).unwrap();
+
+        assert_eq!(
+            file.target(),
+            Some(WatchTarget {
+                dir: dir.join("a/b"),
+                recursive: false,
+                complete: true,
+            })
+        );
+        assert_eq!(
+            directory.target(),
+            Some(WatchTarget {
+                dir: dir.join("a/c"),
+                recursive: true,
+                complete: true,
+            })
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn rewatch_created_dir() {
+        let dir = temp_dir();
+
+        let (tx, rx) = mpsc::channel::<notify::Result<notify::Event>>();
+        let mut watcher = notify::recommended_watcher(tx).unwrap();
+
+        let paths = vec![WatchedPath {
+            path: dir.join("a/config"),
+            layout: Layout::Directory,
+        }];
+        let mut targets = vec![None; paths.len()];
+
+        assert!(update_watches(&mut watcher, &paths, &mut targets).is_empty());
+        assert_eq!(targets[0].as_ref().map(|target| &target.dir), Some(&dir));
+
+        fs::create_dir_all(dir.join("a/config")).unwrap();
+
+        // like on the event of the creation of `a`
+        assert_eq!(
+            update_watches(&mut watcher, &paths, &mut targets),
+            vec![&paths[0]]
+        );
+        assert!(targets[0].as_ref().is_some_and(|target| target.complete));
+
+        // nothing changed since
+        assert!(update_watches(&mut watcher, &paths, &mut targets).is_empty());
+
+        let file = dir.join("a/config/b");
+        fs::write(&file, "1").unwrap();
+
+        // the events stop when nothing happened for a while
+        let seen = std::iter::from_fn(|| rx.recv_timeout(Duration::from_secs(5)).ok())
+            .filter_map(Result::ok)
+            .any(|event| event.paths.contains(&file));
+        assert!(seen, "the change in the created directory was not seen");
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
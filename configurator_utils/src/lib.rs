@@ -6,6 +6,7 @@ use anyhow::anyhow;
 pub enum ConfigFormat {
     Json,
     CosmicRon,
+    Toml,
 }
 
 impl Display for ConfigFormat {
@@ -13,6 +14,7 @@ impl Display for ConfigFormat {
         match self {
             ConfigFormat::Json => write!(f, "json"),
             ConfigFormat::CosmicRon => write!(f, "cosmic_ron"),
+            ConfigFormat::Toml => write!(f, "toml"),
         }
     }
 }
@@ -24,6 +26,7 @@ impl TryFrom<&str> for ConfigFormat {
         let format = match value {
             "json" => ConfigFormat::Json,
             "cosmic_ron" => ConfigFormat::CosmicRon,
+            "toml" => ConfigFormat::Toml,
             _ => Err(anyhow!("unknown format: {}", value))?,
         };
         Ok(format)
@@ -0,0 +1,58 @@
+//! Compare the parsers on the config files of the COSMIC panel:
+//! `cargo bench -p ron_value`
+
+use std::{
+    fs,
+    hint::black_box,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+const ITERATIONS: u32 = 2000;
+
+fn bench(name: &str, files: &[String], parse: impl Fn(&str) -> ron_value::Document) -> Duration {
+    // warm up
+    for file in files {
+        black_box(parse(file));
+    }
+
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        for file in files {
+            black_box(parse(black_box(file)));
+        }
+    }
+
+    let per_iteration = start.elapsed() / ITERATIONS;
+    println!("{name}: {per_iteration:?} for {} files", files.len());
+    per_iteration
+}
+
+fn main() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("benches/panel");
+
+    let files = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| fs::read_to_string(entry.unwrap().path()).unwrap())
+        .collect::<Vec<_>>();
+
+    for file in &files {
+        assert_eq!(
+            ron_value::document_from_str(file).unwrap(),
+            ron_value::document_from_str_combinator(file).unwrap()
+        );
+    }
+
+    let fast = bench("bytes", &files, |file| {
+        ron_value::document_from_str(file).unwrap()
+    });
+    let combinator = bench("combinator", &files, |file| {
+        ron_value::document_from_str_combinator(file).unwrap()
+    });
+
+    println!(
+        "{:.1}x faster",
+        combinator.as_secs_f64() / fast.as_secs_f64()
+    );
+}
@@ -3,28 +3,111 @@ use std::num::NonZero;
 use std::num::ParseFloatError;
 use std::num::TryFromIntError;
 
+use crate::Extensions;
 use crate::F32;
 use crate::F64;
 use crate::Map;
 use crate::Number;
 use crate::Value;
+use crate::error::{self, Error, expected};
+use crate::fast_de;
 use pom::parser::*;
 use unicode_ident::{is_xid_continue, is_xid_start};
 
-pub fn from_str(input: &str) -> Result<Value, pom::Error> {
+/// A ron file, with the extensions enabled by its attributes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Document {
+    pub extensions: Extensions,
+    pub value: Value,
+}
+
+/// Parse a ron file. Its extensions are not returned, see [`document_from_str`].
+pub fn from_str(input: &str) -> Result<Value, Error> {
+    document_from_str(input).map(|document| document.value)
+}
+
+pub fn document_from_str(input: &str) -> Result<Document, Error> {
+    match fast_de::parse(input) {
+        Some(document) => Ok(document),
+        // it knows where the error is
+        None => document_from_str_combinator(input),
+    }
+}
+
+/// Parse with the combinator parser only, which is much slower.
+/// It is used to compare the two parsers.
+#[doc(hidden)]
+pub fn document_from_str_combinator(input: &str) -> Result<Document, Error> {
     let input = input.chars().collect::<Vec<_>>();
 
-    let parser = ron_file();
-    let value = parser.parse(&input)?;
+    error::parse(ron_file(), &input)
+}
 
-    Ok(value)
+fn ron_file<'a>() -> Parser<'a, char, Document> {
+    (ws() * extensions() + value() - ws() - end_of_file())
+        .map(|(extensions, value)| Document { extensions, value })
 }
 
-fn ron_file<'a>() -> Parser<'a, char, Value> {
-    ws() * value() - ws() - end()
+/// The attributes at the start of a file
+pub(crate) fn extensions<'a>() -> Parser<'a, char, Extensions> {
+    (attribute() - ws()).repeat(0..).map(|attributes| {
+        let mut extensions = Extensions::new();
+
+        for name in attributes.into_iter().flatten() {
+            extensions.enable(name);
+        }
+
+        extensions
+    })
 }
 
-fn ws<'a>() -> Parser<'a, char, ()> {
+/// `#![enable(name, ..)]`
+fn attribute<'a>() -> Parser<'a, char, Vec<&'static str>> {
+    (seq(&['#', '!'])
+        * token('[', "`[`")
+        * ws()
+        * expected(seq(&['e', 'n', 'a', 'b', 'l', 'e']), "`enable`")
+        * ws()
+        * token('(', "`(`")
+        * ws()
+        * (extension_name() + (comma() * extension_name()).repeat(0..) - comma().opt()).opt()
+        - ws()
+        - token(')', "`)`")
+        - ws()
+        - token(']', "`]`"))
+    .map(|names| {
+        let mut vec = Vec::new();
+
+        if let Some((first, rest)) = names {
+            vec.push(first);
+            vec.extend(rest);
+        }
+
+        vec
+    })
+}
+
+fn extension_name<'a>() -> Parser<'a, char, &'static str> {
+    let name = ident().convert(|name| {
+        Extensions::NAMES
+            .into_iter()
+            .find(|known| *known == name)
+            .ok_or("unknown extension")
+    });
+
+    expected(name, "an extension")
+}
+
+pub(crate) fn end_of_file<'a>() -> Parser<'a, char, ()> {
+    expected(end(), "the end of the file")
+}
+
+/// A structural char, like `,` or `)`
+pub(crate) fn token<'a>(c: char, name: &'static str) -> Parser<'a, char, char> {
+    expected(sym(c), name)
+}
+
+pub(crate) fn ws<'a>() -> Parser<'a, char, ()> {
     (ws_single() | comment()).repeat(0..).discard()
 }
 
@@ -37,7 +120,7 @@ fn comment<'a>() -> Parser<'a, char, ()> {
 }
 
 fn line_comment<'a>() -> Parser<'a, char, ()> {
-    (seq(&['/', '/']) * none_of("\n").repeat(0..) - sym('\n')).discard()
+    (seq(&['/', '/']) * none_of("\n").repeat(0..) - (sym('\n').discard() | end())).discard()
 }
 
 fn block_comment<'a>() -> Parser<'a, char, ()> {
@@ -69,8 +152,8 @@ fn nested_block_comment<'a>() -> Parser<'a, char, ()> {
     })
 }
 
-fn comma<'a>() -> Parser<'a, char, ()> {
-    (ws() * sym(',') - ws()).discard()
+pub(crate) fn comma<'a>() -> Parser<'a, char, ()> {
+    (ws() * token(',', "`,`") - ws()).discard()
 }
 
 fn digit<'a>() -> Parser<'a, char, char> {
@@ -89,12 +172,13 @@ fn digit_hexadecimal<'a>() -> Parser<'a, char, char> {
     one_of("0123456789ABCDEFabcdef")
 }
 
-fn integer<'a>() -> Parser<'a, char, Number> {
+pub(crate) fn integer<'a>() -> Parser<'a, char, Number> {
     let sign = (sym('-').map(|_| -1) | sym('+').map(|_| 1))
         .opt()
         .map(|s| s.unwrap_or(1));
 
-    (sign + unsigned_decimal() - !sym('.') + integer_suffix().opt()).convert(
+    // the start of a float, like `1.5` or `1e5`
+    (sign + unsigned_decimal() - !one_of(".eE") + integer_suffix().opt()).convert(
         |((sign, digits), suffix)| {
             let default = if sign == 1 { "u128" } else { "i128" };
 
@@ -150,7 +234,7 @@ fn unsigned<'a>() -> Parser<'a, char, u128> {
 }
 
 fn unsigned_binary<'a>() -> Parser<'a, char, u128> {
-    (seq(&['0', 'b']) * digit_binary() + (digit_binary() | sym('_')).repeat(0..)).map(|s| {
+    (seq(&['0', 'b']) * digit_binary() + (digit_binary() | sym('_')).repeat(0..)).convert(|s| {
         let mut res = String::new();
         res.push(s.0);
 
@@ -159,12 +243,12 @@ fn unsigned_binary<'a>() -> Parser<'a, char, u128> {
                 res.push(c);
             }
         }
-        u128::from_str_radix(&res, 2).unwrap()
+        u128::from_str_radix(&res, 2)
     })
 }
 
 fn unsigned_octal<'a>() -> Parser<'a, char, u128> {
-    (seq(&['0', 'o']) * digit_octal() + (digit_octal() | sym('_')).repeat(0..)).map(|s| {
+    (seq(&['0', 'o']) * digit_octal() + (digit_octal() | sym('_')).repeat(0..)).convert(|s| {
         let mut res = String::new();
         res.push(s.0);
 
@@ -173,11 +257,11 @@ fn unsigned_octal<'a>() -> Parser<'a, char, u128> {
                 res.push(c);
             }
         }
-        u128::from_str_radix(&res, 8).unwrap()
+        u128::from_str_radix(&res, 8)
     })
 }
 fn unsigned_hexadecimal<'a>() -> Parser<'a, char, u128> {
-    (seq(&['0', 'x']) * digit_hexadecimal() + (digit_hexadecimal() | sym('_')).repeat(0..)).map(
+    (seq(&['0', 'x']) * digit_hexadecimal() + (digit_hexadecimal() | sym('_')).repeat(0..)).convert(
         |s| {
             let mut res = String::new();
             res.push(s.0);
@@ -187,12 +271,12 @@ fn unsigned_hexadecimal<'a>() -> Parser<'a, char, u128> {
                     res.push(c);
                 }
             }
-            u128::from_str_radix(&res, 16).unwrap()
+            u128::from_str_radix(&res, 16)
         },
     )
 }
 fn unsigned_decimal<'a>() -> Parser<'a, char, u128> {
-    (digit() + (digit() | sym('_')).repeat(0..)).map(|s| {
+    (digit() + (digit() | sym('_')).repeat(0..)).convert(|s| {
         let mut res = String::new();
         res.push(s.0);
 
@@ -202,11 +286,11 @@ fn unsigned_decimal<'a>() -> Parser<'a, char, u128> {
             }
         }
 
-        res.parse::<u128>().unwrap()
+        res.parse::<u128>()
     })
 }
 
-fn byte<'a>() -> Parser<'a, char, u8> {
+pub(crate) fn byte<'a>() -> Parser<'a, char, u8> {
     sym('b') * sym('\'') * byte_content() - sym('\'')
 }
 
@@ -229,12 +313,14 @@ fn ascii<'a>() -> Parser<'a, char, u8> {
     })
 }
 
-fn float<'a>() -> Parser<'a, char, Number> {
+pub(crate) fn float<'a>() -> Parser<'a, char, Number> {
     (one_of("+-").opt()
         + (seq(&['i', 'n', 'f']).map(|_| String::from("inf"))
             | seq(&['N', 'a', 'N']).map(|_| String::from("Nan"))
             | float_num())
-        + float_suffix().opt())
+        + float_suffix().opt()
+        // like `infinity`, which is an identifier
+        - !ident_std_rest())
     .convert(|((sign, mut num), suffix)| {
         if let Some(sign) = sign {
             num.insert(0, sign);
@@ -290,22 +376,17 @@ fn float_frac<'a>() -> Parser<'a, char, String> {
 fn float_exp<'a>() -> Parser<'a, char, String> {
     (one_of("eE")
         + (sym('+') | sym('-')).opt()
-        + (digit() | sym('_')).repeat(0..)
+        + sym('_').repeat(0..)
         + digit()
         + (digit() | sym('_')).repeat(0..))
-    .map(|((((e, sign), digit1), digit2), digit3)| {
+    .map(|((((e, sign), _), digit1), digits)| {
         let mut s = String::new();
         s.push(e);
         if let Some(c) = sign {
             s.push(c);
         }
-        for c in digit1 {
-            if c != '_' {
-                s.push(c);
-            }
-        }
-        s.push(digit2);
-        for c in digit3 {
+        s.push(digit1);
+        for c in digits {
             if c != '_' {
                 s.push(c);
             }
@@ -318,7 +399,7 @@ fn float_suffix<'a>() -> Parser<'a, char, &'static str> {
     seq(&['f', '3', '2']).map(|_| "f32") | seq(&['f', '6', '4']).map(|_| "f64")
 }
 
-fn string<'a>() -> Parser<'a, char, String> {
+pub(crate) fn string<'a>() -> Parser<'a, char, String> {
     string_std() | string_raw()
 }
 
@@ -363,7 +444,7 @@ fn string_raw_content<'a>() -> Parser<'a, char, String> {
 
         let mut content = String::new();
 
-        'outer: while pos < input.len() {
+        while pos < input.len() {
             if input[pos] == '"' {
                 // Check for matching trailing #'s
                 let mut match_hash = true;
@@ -376,7 +457,7 @@ fn string_raw_content<'a>() -> Parser<'a, char, String> {
 
                 if match_hash {
                     pos += 1 + hash_count; // skip closing quote + hashes
-                    break 'outer;
+                    return Ok((content, pos));
                 } else {
                     content.push('"');
                     pos += 1;
@@ -387,7 +468,8 @@ fn string_raw_content<'a>() -> Parser<'a, char, String> {
             }
         }
 
-        Ok((content, pos))
+        // not terminated
+        Err(pom::Error::Incomplete)
     })
 }
 
@@ -402,22 +484,24 @@ fn escape_ascii<'a>() -> Parser<'a, char, char> {
 }
 
 fn escape_byte<'a>() -> Parser<'a, char, u8> {
-    (sym('x') * digit_hexadecimal() + digit_hexadecimal()).map(|(high, low)| {
+    (sym('x') * digit_hexadecimal() + digit_hexadecimal()).convert(|(high, low)| {
         let str = [high, low].iter().collect::<String>();
-        u8::from_str_radix(&str, 16).unwrap()
+        u8::from_str_radix(&str, 16)
     })
 }
 
 fn escape_unicode<'a>() -> Parser<'a, char, char> {
     sym('u')
-        * digit_hexadecimal().repeat(1..=6).map(|s| {
+        * sym('{')
+        * digit_hexadecimal().repeat(1..=6).convert(|s| {
             let s = s.into_iter().collect::<String>();
-            let code = u32::from_str_radix(&s, 16).unwrap();
-            std::char::from_u32(code).unwrap()
+            let code = u32::from_str_radix(&s, 16).map_err(|e| e.to_string())?;
+            std::char::from_u32(code).ok_or_else(|| format!("invalid char code {code:x}"))
         })
+        - sym('}')
 }
 
-fn byte_string<'a>() -> Parser<'a, char, Vec<u8>> {
+pub(crate) fn byte_string<'a>() -> Parser<'a, char, Vec<u8>> {
     byte_string_std() | byte_string_raw()
 }
 
@@ -448,25 +532,34 @@ fn no_double_quote_or_escape_bytes<'a>() -> Parser<'a, char, u8> {
         | (sym('\\') * (escape_ascii().map(|c| c as u8) | escape_byte()))
 }
 
-fn char<'a>() -> Parser<'a, char, char> {
+pub(crate) fn char<'a>() -> Parser<'a, char, char> {
     sym('\'') * (none_of("'\\") | (sym('\\') * (sym('\\').map(|_| '\\') | sym('\'').map(|_| '\''))))
         - sym('\'')
 }
 
-fn bool<'a>() -> Parser<'a, char, bool> {
-    seq(&['t', 'r', 'u', 'e']).map(|_| true) | seq(&['f', 'a', 'l', 's', 'e']).map(|_| false)
+pub(crate) fn bool<'a>() -> Parser<'a, char, bool> {
+    keyword(&['t', 'r', 'u', 'e']).map(|_| true)
+        | keyword(&['f', 'a', 'l', 's', 'e']).map(|_| false)
+}
+
+/// A word which is not the start of an identifier, like `truex`
+pub(crate) fn keyword<'a>(word: &'static [char]) -> Parser<'a, char, ()> {
+    (seq(word) - !ident_std_rest()).discard()
 }
 
 fn option<'a>() -> Parser<'a, char, Option<Value>> {
-    seq(&['N', 'o', 'n', 'e']).map(|_| None) | option_some()
+    keyword(&['N', 'o', 'n', 'e']).map(|_| None) | option_some()
 }
 
 fn option_some<'a>() -> Parser<'a, char, Option<Value>> {
-    (seq(&['S', 'o', 'm', 'e']) * ws() * sym('(') * ws() * call(value) - ws() - sym(')')).map(Some)
+    (seq(&['S', 'o', 'm', 'e']) * ws() * token('(', "`(`") * ws() * call(value)
+        - ws()
+        - token(')', "`)`"))
+    .map(Some)
 }
 
 fn value<'a>() -> Parser<'a, char, Value> {
-    integer().map(Value::Number)
+    let value = integer().map(Value::Number)
         | float().map(Value::Number)
         | byte().map(|b| Value::Bytes(vec![b]))
         | string().map(Value::String)
@@ -477,12 +570,15 @@ fn value<'a>() -> Parser<'a, char, Value> {
         | list().map(Value::List)
         | map().map(Value::Map)
         | tuple().map(Value::Tuple)
-        | struct_()
+        | struct_();
+
+    expected(value, "a value")
 }
 
 fn list<'a>() -> Parser<'a, char, Vec<Value>> {
     (sym('[') * ws() * (call(value) + (comma() * call(value)).repeat(0..) - comma().opt()).opt()
-        - sym(']'))
+        - ws()
+        - token(']', "`]`"))
     .map(|v| {
         let mut vec: Vec<Value> = Vec::new();
 
@@ -496,8 +592,9 @@ fn list<'a>() -> Parser<'a, char, Vec<Value>> {
 }
 
 fn map<'a>() -> Parser<'a, char, Map<Value>> {
-    (sym('{') * (map_entry() + (comma() * map_entry()).repeat(0..) - comma().opt()).opt()
-        - sym('}'))
+    (sym('{') * ws() * (map_entry() + (comma() * map_entry()).repeat(0..) - comma().opt()).opt()
+        - ws()
+        - token('}', "`}`"))
     .map(|v| {
         let mut map: Map<Value> = Map::new();
 
@@ -514,12 +611,13 @@ fn map<'a>() -> Parser<'a, char, Map<Value>> {
 }
 
 fn map_entry<'a>() -> Parser<'a, char, (Value, Value)> {
-    call(value) - ws() - sym(':') - ws() + call(value)
+    call(value) - ws() - token(':', "`:`") - ws() + call(value)
 }
 
 fn tuple<'a>() -> Parser<'a, char, Vec<Value>> {
-    (sym('(') * (call(value) + (comma() * call(value)).repeat(0..) - comma().opt()).opt()
-        - sym(')'))
+    (sym('(') * ws() * (call(value) + (comma() * call(value)).repeat(0..) - comma().opt()).opt()
+        - ws()
+        - token(')', "`)`"))
     .map(|v| {
         let mut vec: Vec<Value> = Vec::new();
 
@@ -551,7 +649,7 @@ fn named_struct<'a>() -> Parser<'a, char, Value> {
     (ident().opt() - ws() - sym('(') - ws()
         + (named_field() + (comma() * named_field()).repeat(0..) - comma().opt()).opt()
         - ws()
-        - sym(')'))
+        - token(')', "`)`"))
     .map(|(ident, v)| {
         let mut map = Map::new();
 
@@ -568,11 +666,12 @@ fn named_struct<'a>() -> Parser<'a, char, Value> {
 }
 
 fn named_field<'a>() -> Parser<'a, char, (String, Value)> {
-    ident() - ws() - sym(':') - ws() + call(value)
+    expected(ident(), "a field name") - ws() - token(':', "`:`") - ws() + call(value)
 }
 
-fn ident<'a>() -> Parser<'a, char, String> {
-    ident_std() | ident_raw()
+pub(crate) fn ident<'a>() -> Parser<'a, char, String> {
+    // `r` is also a standard identifier
+    ident_raw() | ident_std()
 }
 
 fn ident_std<'a>() -> Parser<'a, char, String> {
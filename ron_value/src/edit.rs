@@ -0,0 +1,295 @@
+//! Rewrite a ron source to a new value, keeping the comments
+//! and the layout of the parts that didn't change.
+
+use std::{fmt, ops::Range};
+
+use crate::{
+    Error, PrettyConfig, SerializeError, Value,
+    ser::{ident_to_string, number_to_string},
+    spanned::{self, Entry, Spanned, SpannedKind},
+    to_string, to_string_pretty,
+};
+
+#[derive(Debug)]
+pub enum EditError {
+    Parse(Error),
+    Serialize(SerializeError),
+}
+
+impl fmt::Display for EditError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EditError::Parse(e) => write!(f, "can't parse the source: {e}"),
+            EditError::Serialize(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for EditError {}
+
+impl From<Error> for EditError {
+    fn from(value: Error) -> Self {
+        EditError::Parse(value)
+    }
+}
+
+impl From<SerializeError> for EditError {
+    fn from(value: SerializeError) -> Self {
+        EditError::Serialize(value)
+    }
+}
+
+/// Replace the parts of `source` which are different from `value`.
+///
+/// If nothing changed, the source is returned as is.
+pub fn edit(source: &str, value: &Value) -> Result<String, EditError> {
+    let mut chars = source.chars().collect::<Vec<_>>();
+
+    let spanned = spanned::from_chars(&chars)?;
+
+    let mut editor = Editor {
+        source: &chars,
+        edits: Vec::new(),
+    };
+    editor.diff(&spanned, value)?;
+
+    let mut edits = editor.edits;
+
+    if edits.is_empty() {
+        return Ok(source.to_string());
+    }
+
+    // edits don't overlap, so we can apply them from the end
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+
+    for (range, text) in edits {
+        chars.splice(range, text.chars());
+    }
+
+    Ok(chars.into_iter().collect())
+}
+
+struct Editor<'a> {
+    source: &'a [char],
+    /// Ranges of the source to replace
+    edits: Vec<(Range<usize>, String)>,
+}
+
+impl Editor<'_> {
+    fn diff(&mut self, old: &Spanned, new: &Value) -> Result<(), SerializeError> {
+        if equivalent(&old.to_value(), new) {
+            return Ok(());
+        }
+
+        match (&old.kind, new) {
+            (SpannedKind::Option(old), Value::Option(Some(new))) => self.diff(old, new)?,
+            (SpannedKind::List(olds), Value::List(news))
+            | (SpannedKind::Tuple(None, olds), Value::Tuple(news))
+                if olds.len() == news.len() =>
+            {
+                for (old, new) in olds.iter().zip(news) {
+                    self.diff(old, new)?;
+                }
+            }
+            (SpannedKind::Tuple(Some(name), olds), Value::NamedTuple(new_name, news))
+                if name == new_name && olds.len() == news.len() =>
+            {
+                for (old, new) in olds.iter().zip(news) {
+                    self.diff(old, new)?;
+                }
+            }
+            (SpannedKind::Map(entries), Value::Map(map)) => self.diff_entries(
+                old,
+                new,
+                entries,
+                map.iter().collect(),
+                |old, new| equivalent(&old.to_value(), new),
+                to_string,
+            )?,
+            // structs don't need their name, so we keep the one of the source
+            (SpannedKind::Struct(name, fields), Value::Struct(new_name, map))
+                if name.is_none() || name == new_name =>
+            {
+                self.diff_entries(
+                    old,
+                    new,
+                    fields,
+                    map.iter().collect(),
+                    |old, new| old == new,
+                    |key| ident_to_string(key),
+                )?
+            }
+            _ => self.edits.push((old.span.clone(), to_string(new)?)),
+        }
+
+        Ok(())
+    }
+
+    /// Diff the entries of a map or the fields of a struct.
+    /// Removed entries are deleted with their separator, new ones are added at the end.
+    fn diff_entries<OK, NK>(
+        &mut self,
+        old: &Spanned,
+        new_value: &Value,
+        entries: &[Entry<OK>],
+        new: Vec<(&NK, &Value)>,
+        same_key: impl Fn(&OK, &NK) -> bool,
+        key_to_string: impl Fn(&NK) -> Result<String, SerializeError>,
+    ) -> Result<(), SerializeError> {
+        let new_value_of = |entry: &Entry<OK>| {
+            new.iter()
+                .find(|(key, _)| same_key(&entry.key, key))
+                .map(|(_, value)| *value)
+        };
+
+        let Some(first_kept) = entries
+            .iter()
+            .position(|entry| new_value_of(entry).is_some())
+        else {
+            // nothing to keep
+            self.edits.push((old.span.clone(), to_string(new_value)?));
+            return Ok(());
+        };
+
+        // the entries before the first kept one are removed with the separator that follow them
+        if first_kept > 0 {
+            self.edits.push((
+                entries[0].span.start..entries[first_kept].span.start,
+                String::new(),
+            ));
+        }
+
+        for (pos, entry) in entries.iter().enumerate().skip(first_kept) {
+            match new_value_of(entry) {
+                Some(value) => self.diff(&entry.value, value)?,
+                // remove the separator before the entry
+                None => self
+                    .edits
+                    .push((entries[pos - 1].span.end..entry.span.end, String::new())),
+            }
+        }
+
+        let layout = Layout::of(self.source, old, entries);
+        let mut added = String::new();
+
+        for (key, value) in &new {
+            if !entries.iter().any(|entry| same_key(&entry.key, key)) {
+                added.push_str(&layout.separator());
+                added.push_str(&key_to_string(key)?);
+                added.push_str(": ");
+                added.push_str(&layout.value_to_string(value)?);
+            }
+        }
+
+        if !added.is_empty() {
+            let end = entries.last().unwrap().span.end;
+            self.edits.push((end..end, added));
+        }
+
+        Ok(())
+    }
+}
+
+/// How the entries of a collection are laid out in the source
+enum Layout {
+    /// All on one line
+    Inline,
+    /// One per line
+    Lines {
+        /// Indentation of the entries
+        indent: String,
+        /// Indentation of one level of nesting
+        indentor: String,
+    },
+}
+
+impl Layout {
+    /// The first entry can be on the line of the opening delimiter, so we look at the second one
+    fn of<K>(source: &[char], collection: &Spanned, entries: &[Entry<K>]) -> Self {
+        let Some(entry) = entries.get(1).or(entries.first()) else {
+            return Layout::Inline;
+        };
+
+        let Some(indent) = line_indent(source, entry.span.start) else {
+            return Layout::Inline;
+        };
+
+        // the closing delimiter is at the indentation of the collection
+        let indentor = line_indent(source, collection.span.end - 1)
+            .and_then(|outer| indent.strip_prefix(outer.as_str()).map(String::from))
+            .filter(|indentor| !indentor.is_empty())
+            .unwrap_or_else(|| "    ".to_string());
+
+        Layout::Lines { indent, indentor }
+    }
+
+    fn separator(&self) -> String {
+        match self {
+            Layout::Inline => String::from(", "),
+            Layout::Lines { indent, .. } => format!(",\n{indent}"),
+        }
+    }
+
+    fn value_to_string(&self, value: &Value) -> Result<String, SerializeError> {
+        match self {
+            Layout::Inline => to_string(value),
+            Layout::Lines { indent, indentor } => to_string_pretty(
+                value,
+                &PrettyConfig::new()
+                    .new_line(format!("\n{indent}"))
+                    .indentor(indentor.as_str()),
+            ),
+        }
+    }
+}
+
+/// The whitespace before `pos`, if it is the first token of its line
+fn line_indent(source: &[char], pos: usize) -> Option<String> {
+    let line_start = source[..pos]
+        .iter()
+        .rposition(|c| *c == '\n')
+        .map_or(0, |newline| newline + 1);
+    let before = &source[line_start..pos];
+
+    before
+        .iter()
+        .all(|c| c.is_whitespace())
+        .then(|| before.iter().collect())
+}
+
+/// Same value, ignoring the representation of numbers,
+/// which depends on the type used to read them
+fn equivalent(a: &Value, b: &Value) -> bool {
+    fn all_equivalent(a: &[Value], b: &[Value]) -> bool {
+        a.len() == b.len() && a.iter().zip(b).all(|(a, b)| equivalent(a, b))
+    }
+
+    match (a, b) {
+        (Value::Number(a), Value::Number(b)) => number_to_string(a) == number_to_string(b),
+        // `()` is parsed as an empty tuple
+        (Value::Unit, Value::Tuple(values)) | (Value::Tuple(values), Value::Unit) => {
+            values.is_empty()
+        }
+        (Value::Option(Some(a)), Value::Option(Some(b))) => equivalent(a, b),
+        (Value::List(a), Value::List(b)) | (Value::Tuple(a), Value::Tuple(b)) => {
+            all_equivalent(a, b)
+        }
+        (Value::NamedTuple(name_a, a), Value::NamedTuple(name_b, b)) => {
+            name_a == name_b && all_equivalent(a, b)
+        }
+        (Value::Map(a), Value::Map(b)) => {
+            a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|((ka, va), (kb, vb))| equivalent(ka, kb) && equivalent(va, vb))
+        }
+        (Value::Struct(name_a, a), Value::Struct(name_b, b)) => {
+            (name_a.is_none() || name_a == name_b)
+                && a.len() == b.len()
+                && a.iter()
+                    .zip(b.iter())
+                    .all(|((ka, va), (kb, vb))| ka == kb && equivalent(va, vb))
+        }
+        (a, b) => a == b,
+    }
+}
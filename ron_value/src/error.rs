@@ -0,0 +1,179 @@
+//! Parse errors with their position in the source.
+//!
+//! The parser backtracks, so the error of pom is the one of the last
+//! alternative tried, usually at the start of the value. Instead, the
+//! tokens which can be expected record where they failed, and the error
+//! is reported at the furthest position the parser reached.
+
+use std::{cell::RefCell, fmt};
+
+use pom::parser::Parser;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Error {
+    line: usize,
+    column: usize,
+    offset: usize,
+    found: String,
+    expected: Vec<&'static str>,
+    snippet: String,
+}
+
+impl Error {
+    fn new(input: &[char], offset: usize, expected: Vec<&'static str>) -> Self {
+        let line_start = input[..offset]
+            .iter()
+            .rposition(|c| *c == '\n')
+            .map_or(0, |pos| pos + 1);
+        let line_end = input[offset..]
+            .iter()
+            .position(|c| *c == '\n')
+            .map_or(input.len(), |pos| offset + pos);
+
+        let line = input[..line_start].iter().filter(|c| **c == '\n').count() + 1;
+        let column = offset - line_start + 1;
+
+        Self {
+            line,
+            column,
+            offset,
+            found: found(&input[offset..]),
+            expected,
+            snippet: snippet(line, &input[line_start..line_end], column),
+        }
+    }
+
+    /// Starting at 1
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Starting at 1, in chars
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// In chars
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The token at the position of the error
+    pub fn found(&self) -> &str {
+        &self.found
+    }
+
+    /// What could have been parsed at the position of the error
+    pub fn expected(&self) -> &[&'static str] {
+        &self.expected
+    }
+
+    /// The line of the error, with a caret under its position
+    pub fn snippet(&self) -> &str {
+        &self.snippet
+    }
+
+    /// The error without its position
+    pub fn message(&self) -> String {
+        let found = &self.found;
+
+        match self.expected.as_slice() {
+            [] => format!("unexpected {found}"),
+            [expected] => format!("expected {expected}, found {found}"),
+            [expected @ .., last] => {
+                format!("expected {} or {last}, found {found}", expected.join(", "))
+            }
+        }
+    }
+}
+
+/// `line:column: message`, followed by the snippet with `{:#}`
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message())?;
+
+        if f.alternate() {
+            write!(f, "\n{}", self.snippet)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn found(rest: &[char]) -> String {
+    let is_word = |c: &char| c.is_alphanumeric() || *c == '_';
+
+    match rest.first() {
+        None => "the end of the file".to_string(),
+        Some(c) if is_word(c) => {
+            let word = rest.iter().take_while(|c| is_word(c)).collect::<String>();
+            format!("`{word}`")
+        }
+        Some(c) => format!("`{c}`"),
+    }
+}
+
+fn snippet(line: usize, content: &[char], column: usize) -> String {
+    let number = line.to_string();
+    let margin = " ".repeat(number.len());
+
+    // keep the tabs, so the caret is aligned
+    let caret_indent = content
+        .iter()
+        .take(column - 1)
+        .map(|c| if *c == '\t' { '\t' } else { ' ' })
+        .collect::<String>();
+
+    format!(
+        "{margin} |\n{number} | {}\n{margin} | {caret_indent}^",
+        content.iter().collect::<String>()
+    )
+}
+
+#[derive(Default)]
+struct Furthest {
+    offset: usize,
+    expected: Vec<&'static str>,
+}
+
+thread_local! {
+    static FURTHEST: RefCell<Furthest> = RefCell::default();
+}
+
+/// Record that `name` was expected where `parser` failed
+pub(crate) fn expected<'a, O: 'a>(
+    parser: Parser<'a, char, O>,
+    name: &'static str,
+) -> Parser<'a, char, O> {
+    Parser::new(move |input, start| {
+        let res = parser.parse_at(input, start);
+
+        if res.is_err() {
+            FURTHEST.with_borrow_mut(|furthest| {
+                if start > furthest.offset {
+                    furthest.offset = start;
+                    furthest.expected.clear();
+                }
+
+                if start == furthest.offset && !furthest.expected.contains(&name) {
+                    furthest.expected.push(name);
+                }
+            });
+        }
+
+        res
+    })
+}
+
+/// Parse all the input, reporting the error at the furthest position reached
+pub(crate) fn parse<'a, O>(parser: Parser<'a, char, O>, input: &'a [char]) -> Result<O, Error> {
+    FURTHEST.set(Furthest::default());
+
+    let res = parser.parse(input);
+
+    let furthest = FURTHEST.take();
+
+    res.map_err(|_| Error::new(input, furthest.offset, furthest.expected))
+}
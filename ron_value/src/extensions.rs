@@ -0,0 +1,67 @@
+//! Extensions of ron, enabled by attributes at the start of a file,
+//! like `#![enable(implicit_some)]`.
+
+/// The extensions enabled by a file. They don't change how the file is
+/// parsed to a [`Value`](crate::Value), but how it maps to a type, see
+/// [`from_value_with_extensions`](crate::from_value_with_extensions).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Extensions {
+    /// Newtype structs are written as their content: `1` instead of `(1)`
+    pub unwrap_newtypes: bool,
+    /// `Some` can be omitted: `1` is read as `Some(1)`
+    pub implicit_some: bool,
+    /// A newtype variant shares its parentheses with the struct or the
+    /// tuple it contains: `Variant(a: 1)` instead of `Variant((a: 1))`
+    pub unwrap_variant_newtypes: bool,
+    /// Struct names are required
+    pub explicit_struct_names: bool,
+}
+
+impl Extensions {
+    /// Names in the attributes, in the order ron writes them
+    pub const NAMES: [&'static str; 4] = [
+        "unwrap_newtypes",
+        "implicit_some",
+        "unwrap_variant_newtypes",
+        "explicit_struct_names",
+    ];
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn get_mut(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "unwrap_newtypes" => Some(&mut self.unwrap_newtypes),
+            "implicit_some" => Some(&mut self.implicit_some),
+            "unwrap_variant_newtypes" => Some(&mut self.unwrap_variant_newtypes),
+            "explicit_struct_names" => Some(&mut self.explicit_struct_names),
+            _ => None,
+        }
+    }
+
+    /// Return `false` if the extension is unknown
+    pub fn enable(&mut self, name: &str) -> bool {
+        match self.get_mut(name) {
+            Some(enabled) => {
+                *enabled = true;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn is_enabled(&self, name: &str) -> bool {
+        let mut extensions = *self;
+        extensions.get_mut(name).is_some_and(|enabled| *enabled)
+    }
+
+    /// Names of the enabled extensions
+    pub fn names(&self) -> impl Iterator<Item = &'static str> + '_ {
+        Self::NAMES.into_iter().filter(|name| self.is_enabled(name))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names().next().is_none()
+    }
+}
@@ -0,0 +1,689 @@
+//! A parser over the bytes of the input, with the grammar of [`crate::de`].
+//!
+//! It doesn't collect the input into chars nor allocates for each token,
+//! which makes it much faster than the combinator parser. It doesn't
+//! report errors: when it fails, the combinator parser runs again to find
+//! where the error is. It never gives another value than the combinator
+//! parser.
+
+use unicode_ident::{is_xid_continue, is_xid_start};
+
+use crate::{Document, Extensions, F32, F64, Map, Number, Value};
+
+pub(crate) fn parse(input: &str) -> Option<Document> {
+    let mut parser = Parser {
+        input,
+        bytes: input.as_bytes(),
+        pos: 0,
+    };
+
+    parser.ws()?;
+    let extensions = parser.extensions()?;
+    let value = parser.value()?;
+    parser.ws()?;
+
+    (parser.pos == parser.bytes.len()).then_some(Document { extensions, value })
+}
+
+struct Parser<'a> {
+    input: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(self.pos + offset).copied()
+    }
+
+    fn peek_char(&self) -> Option<char> {
+        self.input[self.pos..].chars().next()
+    }
+
+    fn starts_with(&self, s: &[u8]) -> bool {
+        self.bytes[self.pos..].starts_with(s)
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        let found = self.peek() == Some(b);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn expect(&mut self, b: u8) -> Option<()> {
+        self.eat(b).then_some(())
+    }
+
+    fn expect_seq(&mut self, s: &[u8]) -> Option<()> {
+        self.starts_with(s).then(|| self.pos += s.len())
+    }
+
+    /// Whitespaces and comments
+    fn ws(&mut self) -> Option<()> {
+        while let Some(b) = self.peek() {
+            match b {
+                b'\n' | b'\t' | b'\r' | b' ' | 0x0B | 0x0C => self.pos += 1,
+                b'/' if self.peek_at(1) == Some(b'/') => {
+                    match self.bytes[self.pos..].iter().position(|b| *b == b'\n') {
+                        Some(len) => self.pos += len + 1,
+                        None => self.pos = self.bytes.len(),
+                    }
+                }
+                b'/' if self.peek_at(1) == Some(b'*') => self.block_comment()?,
+                0x80.. => match self.peek_char() {
+                    Some(c @ ('\u{0085}' | '\u{200E}' | '\u{200F}' | '\u{2028}' | '\u{2029}')) => {
+                        self.pos += c.len_utf8()
+                    }
+                    _ => break,
+                },
+                _ => break,
+            }
+        }
+
+        Some(())
+    }
+
+    fn block_comment(&mut self) -> Option<()> {
+        self.pos += 2;
+        let mut depth = 1;
+
+        while depth > 0 {
+            match (self.peek()?, self.peek_at(1)) {
+                (b'*', Some(b'/')) => {
+                    depth -= 1;
+                    self.pos += 2;
+                }
+                (b'/', Some(b'*')) => {
+                    depth += 1;
+                    self.pos += 2;
+                }
+                _ => self.pos += 1,
+            }
+        }
+
+        Some(())
+    }
+
+    /// `ws ',' ws`, if there is a comma
+    fn comma(&mut self) -> Option<bool> {
+        let start = self.pos;
+        self.ws()?;
+
+        if self.eat(b',') {
+            self.ws()?;
+            Some(true)
+        } else {
+            self.pos = start;
+            Some(false)
+        }
+    }
+
+    /// Elements separated by commas, with an optional trailing comma
+    fn elements<T>(
+        &mut self,
+        close: u8,
+        mut element: impl FnMut(&mut Self) -> Option<T>,
+    ) -> Option<Vec<T>> {
+        let mut elements = Vec::new();
+
+        self.ws()?;
+
+        if self.eat(close) {
+            return Some(elements);
+        }
+
+        loop {
+            elements.push(element(self)?);
+
+            let comma = self.comma()?;
+            self.ws()?;
+
+            if self.eat(close) {
+                return Some(elements);
+            }
+
+            if !comma {
+                return None;
+            }
+        }
+    }
+
+    fn extensions(&mut self) -> Option<Extensions> {
+        let mut extensions = Extensions::new();
+
+        while self.starts_with(b"#!") {
+            self.pos += 2;
+            self.expect(b'[')?;
+            self.ws()?;
+            self.expect_seq(b"enable")?;
+            self.ws()?;
+            self.expect(b'(')?;
+
+            for name in self.elements(b')', |parser| parser.ident())? {
+                if !extensions.enable(name) {
+                    return None;
+                }
+            }
+
+            self.ws()?;
+            self.expect(b']')?;
+            self.ws()?;
+        }
+
+        Some(extensions)
+    }
+
+    fn value(&mut self) -> Option<Value> {
+        match self.peek()? {
+            b'0'..=b'9' | b'+' | b'-' | b'.' => self.number().map(Value::Number),
+            b'"' => self.string().map(Value::String),
+            b'\'' => self.char().map(Value::Char),
+            b'[' => {
+                self.pos += 1;
+                self.elements(b']', Self::value).map(Value::List)
+            }
+            b'{' => self.map().map(Value::Map),
+            b'(' => self.parenthesized(None),
+            b'b' if self.peek_at(1) == Some(b'\'') => self.byte().map(|b| Value::Bytes(vec![b])),
+            b'b' if self.peek_at(1) == Some(b'"') => self.byte_string().map(Value::Bytes),
+            b'b' if self.peek_at(1) == Some(b'r') && self.is_raw_string_at(2) => {
+                self.pos += 2;
+                let s = self.raw_string_content()?;
+                s.is_ascii().then(|| Value::Bytes(s.as_bytes().to_vec()))
+            }
+            b'r' if self.is_raw_string_at(1) => {
+                self.pos += 1;
+                self.raw_string_content()
+                    .map(|s| Value::String(s.to_string()))
+            }
+            _ => self.ident_value(),
+        }
+    }
+
+    /// Values starting with an identifier: keywords and structs
+    fn ident_value(&mut self) -> Option<Value> {
+        let raw = self.starts_with(b"r#");
+        let name = self.ident()?;
+
+        if !raw {
+            let keyword = match name {
+                "true" => Some(Value::Bool(true)),
+                "false" => Some(Value::Bool(false)),
+                "None" => Some(Value::Option(None)),
+                "inf" | "inff64" => Some(Value::Number(Number::F64(F64(f64::INFINITY)))),
+                "inff32" => Some(Value::Number(Number::F32(F32(f32::INFINITY)))),
+                "NaN" | "NaNf64" => Some(Value::Number(Number::F64(F64(f64::NAN)))),
+                "NaNf32" => Some(Value::Number(Number::F32(F32(f32::NAN)))),
+                _ => None,
+            };
+
+            if keyword.is_some() {
+                return keyword;
+            }
+        }
+
+        let after_name = self.pos;
+        self.ws()?;
+
+        if self.peek() != Some(b'(') {
+            self.pos = after_name;
+            return Some(Value::UnitStruct(name.to_string()));
+        }
+
+        if !raw && name == "Some" {
+            let start = self.pos;
+
+            if let Some(value) = self.option_some() {
+                return Some(value);
+            }
+
+            // like `Some(1, 2)`, which is a tuple struct
+            self.pos = start;
+        }
+
+        self.parenthesized(Some(name.to_string()))
+    }
+
+    fn option_some(&mut self) -> Option<Value> {
+        self.expect(b'(')?;
+        self.ws()?;
+        let value = self.value()?;
+        self.ws()?;
+        self.expect(b')')?;
+
+        Some(Value::Option(Some(Box::new(value))))
+    }
+
+    /// A tuple or a struct, after its name
+    fn parenthesized(&mut self, name: Option<String>) -> Option<Value> {
+        self.expect(b'(')?;
+
+        if self.is_named_struct()? {
+            let fields = self.elements(b')', |parser| {
+                let key = parser.ident()?;
+                parser.ws()?;
+                parser.expect(b':')?;
+                parser.ws()?;
+                Some((key, parser.value()?))
+            })?;
+
+            let map = fields.into_iter().collect();
+            return Some(Value::Struct(name, map));
+        }
+
+        let values = self.elements(b')', Self::value)?;
+
+        match name {
+            Some(name) => Some(Value::NamedTuple(name, values)),
+            None => Some(Value::Tuple(values)),
+        }
+    }
+
+    /// Whether the first element is a field, like `a: 1`
+    fn is_named_struct(&mut self) -> Option<bool> {
+        let start = self.pos;
+        self.ws()?;
+
+        let is_named = self.ident().is_some() && self.ws().is_some() && self.peek() == Some(b':');
+
+        self.pos = start;
+        Some(is_named)
+    }
+
+    fn map(&mut self) -> Option<Map<Value>> {
+        self.expect(b'{')?;
+
+        let entries = self.elements(b'}', |parser| {
+            let key = parser.value()?;
+            parser.ws()?;
+            parser.expect(b':')?;
+            parser.ws()?;
+            Some((key, parser.value()?))
+        })?;
+
+        let mut map = Map::new();
+
+        for (key, value) in entries {
+            map.insert(key, value);
+        }
+
+        Some(map)
+    }
+
+    fn ident(&mut self) -> Option<&'a str> {
+        let input = self.input;
+
+        if self.starts_with(b"r#") {
+            let start = self.pos + 2;
+            self.pos = start;
+
+            while let Some(c) = self.peek_char()
+                && (is_xid_continue(c) || matches!(c, '.' | '+' | '-'))
+            {
+                self.pos += c.len_utf8();
+            }
+
+            return (self.pos > start).then(|| &input[start..self.pos]);
+        }
+
+        let start = self.pos;
+
+        match self.peek()? {
+            b'a'..=b'z' | b'A'..=b'Z' | b'_' => self.pos += 1,
+            0x80.. => {
+                let c = self.peek_char()?;
+                if !is_xid_start(c) {
+                    return None;
+                }
+                self.pos += c.len_utf8();
+            }
+            _ => return None,
+        }
+
+        while let Some(b) = self.peek() {
+            match b {
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'_' => self.pos += 1,
+                0x80.. => match self.peek_char() {
+                    Some(c) if is_xid_continue(c) => self.pos += c.len_utf8(),
+                    _ => break,
+                },
+                _ => break,
+            }
+        }
+
+        Some(&input[start..self.pos])
+    }
+
+    /// Digits with underscores, starting with a digit
+    fn digits(&mut self) -> Option<&'a str> {
+        let start = self.pos;
+
+        if !self.peek()?.is_ascii_digit() {
+            return None;
+        }
+
+        while let Some(b'0'..=b'9' | b'_') = self.peek() {
+            self.pos += 1;
+        }
+
+        Some(&self.input[start..self.pos])
+    }
+
+    fn number(&mut self) -> Option<Number> {
+        let start = self.pos;
+
+        if let Some(number) = self.integer() {
+            return Some(number);
+        }
+
+        self.pos = start;
+        self.float()
+    }
+
+    fn integer(&mut self) -> Option<Number> {
+        let negative = match self.peek()? {
+            b'-' => {
+                self.pos += 1;
+                true
+            }
+            b'+' => {
+                self.pos += 1;
+                false
+            }
+            _ => false,
+        };
+
+        let digits = self.digits()?;
+
+        // the start of a float, like `1.5` or `1e5`
+        if let Some(b'.' | b'e' | b'E') = self.peek() {
+            return None;
+        }
+
+        let digits: u128 = if digits.contains('_') {
+            digits.replace('_', "").parse().ok()?
+        } else {
+            digits.parse().ok()?
+        };
+
+        let suffix = [
+            "i8", "i16", "i32", "i64", "i128", "u8", "u16", "u32", "u64", "u128",
+        ]
+        .into_iter()
+        .find(|suffix| self.starts_with(suffix.as_bytes()));
+
+        if let Some(suffix) = suffix {
+            self.pos += suffix.len();
+        }
+
+        let sign: i32 = if negative { -1 } else { 1 };
+
+        // like the combinator parser, the sign of unsigned integers is ignored
+        let number = match suffix.unwrap_or(if negative { "i128" } else { "u128" }) {
+            "i8" => Number::I8(i8::try_from(digits).ok()? * sign as i8),
+            "i16" => Number::I16(i16::try_from(digits).ok()? * sign as i16),
+            "i32" => Number::I32(i32::try_from(digits).ok()? * sign),
+            "i64" => Number::I64(i64::try_from(digits).ok()? * i64::from(sign)),
+            "i128" => Number::I128(i128::try_from(digits).ok()? * i128::from(sign)),
+            "u8" => Number::U8(digits.try_into().ok()?),
+            "u16" => Number::U16(digits.try_into().ok()?),
+            "u32" => Number::U32(digits.try_into().ok()?),
+            "u64" => Number::U64(digits.try_into().ok()?),
+            _ => Number::U128(digits),
+        };
+
+        Some(number)
+    }
+
+    fn float(&mut self) -> Option<Number> {
+        let start = self.pos;
+
+        if matches!(self.peek()?, b'+' | b'-') {
+            self.pos += 1;
+        }
+
+        if self.starts_with(b"inf") || self.starts_with(b"NaN") {
+            self.pos += 3;
+        } else {
+            match self.peek()? {
+                b'.' => {
+                    self.pos += 1;
+                    self.digits()?;
+                }
+                _ => {
+                    self.digits()?;
+
+                    if self.eat(b'.') && self.peek().is_some_and(|b| b.is_ascii_digit()) {
+                        self.digits()?;
+                    }
+                }
+            }
+
+            if let Some(b'e' | b'E') = self.peek() {
+                let before_exponent = self.pos;
+                self.pos += 1;
+
+                if let Some(b'+' | b'-') = self.peek() {
+                    self.pos += 1;
+                }
+
+                while let Some(b'_') = self.peek() {
+                    self.pos += 1;
+                }
+
+                if self.digits().is_none() {
+                    self.pos = before_exponent;
+                }
+            }
+        }
+
+        let text = self.input[start..self.pos].replace('_', "");
+
+        if self.starts_with(b"f32") {
+            self.pos += 3;
+            Some(Number::F32(F32(text.parse().ok()?)))
+        } else {
+            self.expect_seq(b"f64");
+            Some(Number::F64(F64(text.parse().ok()?)))
+        }
+    }
+
+    fn hex_digits(&mut self, max: usize) -> Option<u32> {
+        let start = self.pos;
+
+        while self.pos - start < max && self.peek().is_some_and(|b| b.is_ascii_hexdigit()) {
+            self.pos += 1;
+        }
+
+        if self.pos == start {
+            return None;
+        }
+
+        u32::from_str_radix(&self.input[start..self.pos], 16).ok()
+    }
+
+    fn escape_ascii(&mut self) -> Option<char> {
+        let c = match self.peek()? {
+            b'\'' => '\'',
+            b'"' => '"',
+            b'\\' => '\\',
+            b'n' => '\n',
+            b'r' => '\r',
+            b't' => '\t',
+            b'0' => '\0',
+            _ => return None,
+        };
+
+        self.pos += 1;
+        Some(c)
+    }
+
+    fn escape_byte(&mut self) -> Option<u8> {
+        self.expect(b'x')?;
+        let start = self.pos;
+        let byte = self.hex_digits(2)?;
+        (self.pos - start == 2).then_some(byte as u8)
+    }
+
+    fn string(&mut self) -> Option<String> {
+        self.expect(b'"')?;
+
+        let start = self.pos;
+        let len = self.bytes[start..]
+            .iter()
+            .position(|b| matches!(b, b'"' | b'\\'))?;
+        self.pos += len;
+
+        // most strings don't have escapes
+        if self.eat(b'"') {
+            return Some(self.input[start..start + len].to_string());
+        }
+
+        let mut string = self.input[start..self.pos].to_string();
+
+        loop {
+            let len = self.bytes[self.pos..]
+                .iter()
+                .position(|b| matches!(b, b'"' | b'\\'))?;
+            string.push_str(&self.input[self.pos..self.pos + len]);
+            self.pos += len;
+
+            if self.eat(b'"') {
+                return Some(string);
+            }
+
+            self.pos += 1;
+
+            let c = match self.peek()? {
+                b'x' => self.escape_byte()? as char,
+                b'u' => {
+                    self.pos += 1;
+                    self.expect(b'{')?;
+                    let c = char::from_u32(self.hex_digits(6)?)?;
+                    self.expect(b'}')?;
+                    c
+                }
+                _ => self.escape_ascii()?,
+            };
+
+            string.push(c);
+        }
+    }
+
+    fn is_raw_string_at(&self, offset: usize) -> bool {
+        let rest = &self.bytes[self.pos + offset..];
+        let hashes = rest.iter().take_while(|b| **b == b'#').count();
+        rest.get(hashes) == Some(&b'"')
+    }
+
+    /// After the `r`
+    fn raw_string_content(&mut self) -> Option<&'a str> {
+        let hashes = self.bytes[self.pos..]
+            .iter()
+            .take_while(|b| **b == b'#')
+            .count();
+        self.pos += hashes;
+        self.expect(b'"')?;
+
+        let start = self.pos;
+
+        loop {
+            let len = self.bytes[self.pos..].iter().position(|b| *b == b'"')?;
+            self.pos += len + 1;
+
+            let closing = self.bytes[self.pos..]
+                .iter()
+                .take(hashes)
+                .take_while(|b| **b == b'#')
+                .count();
+
+            if closing == hashes {
+                let content = &self.input[start..self.pos - 1];
+                self.pos += hashes;
+                return Some(content);
+            }
+        }
+    }
+
+    fn byte(&mut self) -> Option<u8> {
+        self.expect_seq(b"b'")?;
+
+        let byte = match self.peek()? {
+            b'\\' => {
+                self.pos += 1;
+                match self.peek()? {
+                    b'x' => self.escape_byte()?,
+                    _ => self.escape_ascii()? as u8,
+                }
+            }
+            b'\'' | 0x80.. => return None,
+            b => {
+                self.pos += 1;
+                b
+            }
+        };
+
+        self.expect(b'\'')?;
+        Some(byte)
+    }
+
+    fn byte_string(&mut self) -> Option<Vec<u8>> {
+        self.expect_seq(b"b\"")?;
+
+        let mut bytes = Vec::new();
+
+        loop {
+            match self.peek()? {
+                b'"' => {
+                    self.pos += 1;
+                    return Some(bytes);
+                }
+                b'\\' => {
+                    self.pos += 1;
+                    let byte = match self.peek()? {
+                        b'x' => self.escape_byte()?,
+                        _ => self.escape_ascii()? as u8,
+                    };
+                    bytes.push(byte);
+                }
+                b if b.is_ascii() => {
+                    self.pos += 1;
+                    bytes.push(b);
+                }
+                // like the combinator parser, which truncates the chars
+                _ => {
+                    let c = self.peek_char()?;
+                    self.pos += c.len_utf8();
+                    bytes.push(c as u8);
+                }
+            }
+        }
+    }
+
+    fn char(&mut self) -> Option<char> {
+        self.expect(b'\'')?;
+
+        let c = match self.peek_char()? {
+            '\\' => {
+                self.pos += 1;
+                match self.peek()? {
+                    b'\\' => '\\',
+                    b'\'' => '\'',
+                    _ => return None,
+                }
+            }
+            '\'' => return None,
+            c => c,
+        };
+
+        self.pos += c.len_utf8();
+        self.expect(b'\'')?;
+        Some(c)
+    }
+}
@@ -0,0 +1,677 @@
+//! Deserialize any type from a [`Value`], with the rules of ron:
+//! options must be written `Some(..)`, enum variants are named,
+//! and struct names are optional but checked when present.
+//! The [`Extensions`] of a file relax some of these rules.
+
+use std::fmt;
+
+use serde::{
+    Deserialize,
+    de::{
+        self, DeserializeOwned, DeserializeSeed, EnumAccess, Expected, IntoDeserializer, MapAccess,
+        SeqAccess, Unexpected, VariantAccess, Visitor,
+        value::{MapDeserializer, SeqDeserializer},
+    },
+    forward_to_deserialize_any,
+};
+
+use crate::{Extensions, Map, Number, Value};
+
+/// Error of [`from_value`] and [`to_value`](crate::to_value)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValueError(String);
+
+impl fmt::Display for ValueError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl std::error::Error for ValueError {}
+
+impl de::Error for ValueError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ValueError(msg.to_string())
+    }
+}
+
+impl serde::ser::Error for ValueError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        ValueError(msg.to_string())
+    }
+}
+
+/// Deserialize a `T` from a [`Value`]
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, ValueError> {
+    T::deserialize(value)
+}
+
+impl Value {
+    fn unexpected(&self) -> Unexpected<'_> {
+        match self {
+            Value::Unit => Unexpected::Unit,
+            Value::Bool(b) => Unexpected::Bool(*b),
+            Value::Char(c) => Unexpected::Char(*c),
+            Value::Number(Number::F32(f)) => Unexpected::Float(f64::from(f.get())),
+            Value::Number(Number::F64(f)) => Unexpected::Float(f.get()),
+            Value::Number(_) => Unexpected::Other("integer"),
+            Value::String(s) => Unexpected::Str(s),
+            Value::Bytes(b) => Unexpected::Bytes(b),
+            Value::Option(_) => Unexpected::Option,
+            Value::List(_) => Unexpected::Seq,
+            Value::Map(_) => Unexpected::Map,
+            Value::Tuple(_) => Unexpected::Other("tuple"),
+            Value::UnitStruct(_) => Unexpected::Other("unit struct"),
+            Value::Struct(..) => Unexpected::Other("struct"),
+            Value::NamedTuple(..) => Unexpected::Other("named tuple"),
+        }
+    }
+
+    fn invalid_type(&self, expected: &dyn Expected) -> ValueError {
+        de::Error::invalid_type(self.unexpected(), expected)
+    }
+}
+
+/// Deserialize a `T` from a [`Value`] of a file which enabled `extensions`,
+/// see [`Document`](crate::Document)
+pub fn from_value_with_extensions<T: DeserializeOwned>(
+    value: Value,
+    extensions: Extensions,
+) -> Result<T, ValueError> {
+    T::deserialize(ValueDeserializer::new(value, extensions))
+}
+
+/// A struct name must be the right one when present,
+/// and is optional without the extension `explicit_struct_names`
+fn check_name(
+    found: Option<&str>,
+    expected: &'static str,
+    extensions: Extensions,
+) -> Result<(), ValueError> {
+    match found {
+        Some(found) if found != expected => Err(de::Error::custom(format!(
+            "expected struct {expected}, found {found}"
+        ))),
+        None if extensions.explicit_struct_names => Err(de::Error::custom(format!(
+            "expected struct {expected}, found a struct without name"
+        ))),
+        _ => Ok(()),
+    }
+}
+
+fn visit_number<'de, V: Visitor<'de>>(number: Number, visitor: V) -> Result<V::Value, ValueError> {
+    // the parser reads integers as 128 bits, which most visitors don't support
+    match number {
+        Number::I8(v) => visitor.visit_i8(v),
+        Number::I16(v) => visitor.visit_i16(v),
+        Number::I32(v) => visitor.visit_i32(v),
+        Number::I64(v) => visitor.visit_i64(v),
+        Number::I128(v) => match i64::try_from(v) {
+            Ok(v) => visitor.visit_i64(v),
+            Err(_) => visitor.visit_i128(v),
+        },
+        Number::U8(v) => visitor.visit_u8(v),
+        Number::U16(v) => visitor.visit_u16(v),
+        Number::U32(v) => visitor.visit_u32(v),
+        Number::U64(v) => visitor.visit_u64(v),
+        Number::U128(v) => match u64::try_from(v) {
+            Ok(v) => visitor.visit_u64(v),
+            Err(_) => visitor.visit_u128(v),
+        },
+        Number::F32(v) => visitor.visit_f32(v.get()),
+        Number::F64(v) => visitor.visit_f64(v.get()),
+    }
+}
+
+fn visit_seq<'de, V: Visitor<'de>>(
+    values: Vec<Value>,
+    extensions: Extensions,
+    visitor: V,
+) -> Result<V::Value, ValueError> {
+    let mut deserializer = SeqDeserializer::new(
+        values
+            .into_iter()
+            .map(|value| ValueDeserializer::new(value, extensions)),
+    );
+    let value = visitor.visit_seq(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+fn visit_map<'de, V: Visitor<'de>>(
+    map: Map<Value>,
+    extensions: Extensions,
+    visitor: V,
+) -> Result<V::Value, ValueError> {
+    let mut deserializer = MapDeserializer::new(map.into_iter().map(|(key, value)| {
+        (
+            ValueDeserializer::new(key, extensions),
+            ValueDeserializer::new(value, extensions),
+        )
+    }));
+    let value = visitor.visit_map(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+fn visit_fields<'de, V: Visitor<'de>>(
+    fields: Map<String>,
+    extensions: Extensions,
+    visitor: V,
+) -> Result<V::Value, ValueError> {
+    let mut deserializer = MapDeserializer::new(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key, ValueDeserializer::new(value, extensions))),
+    );
+    let value = visitor.visit_map(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// A variant in serde's externally tagged representation: `{name: payload}`
+fn visit_variant<'de, V: Visitor<'de>>(
+    name: String,
+    payload: Value,
+    extensions: Extensions,
+    visitor: V,
+) -> Result<V::Value, ValueError> {
+    let mut deserializer = MapDeserializer::new(std::iter::once((
+        name,
+        ValueDeserializer::new(payload, extensions),
+    )));
+    let value = visitor.visit_map(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(value)
+}
+
+/// A [`Value`] with the extensions of its file, which also apply to
+/// the values it contains. [`Value`] itself is a deserializer without them.
+#[derive(Debug, Clone)]
+pub struct ValueDeserializer {
+    value: Value,
+    extensions: Extensions,
+}
+
+impl ValueDeserializer {
+    pub fn new(value: Value, extensions: Extensions) -> Self {
+        Self { value, extensions }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = ValueError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let Self { value, extensions } = self;
+
+        match value {
+            Value::Unit => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Char(c) => visitor.visit_char(c),
+            Value::Number(number) => visit_number(number, visitor),
+            Value::String(s) => visitor.visit_string(s),
+            Value::Bytes(b) => visitor.visit_byte_buf(b),
+            Value::Option(None) => visitor.visit_none(),
+            Value::Option(Some(value)) => {
+                visitor.visit_some(ValueDeserializer::new(*value, extensions))
+            }
+            Value::List(values) | Value::Tuple(values) => visit_seq(values, extensions, visitor),
+            Value::Map(map) => visit_map(map, extensions, visitor),
+            Value::Struct(None, fields) => visit_fields(fields, extensions, visitor),
+            // like the `Serialize` implementation of `Value`
+            Value::UnitStruct(name) => visitor.visit_string(name),
+            Value::Struct(Some(name), fields) => {
+                visit_variant(name, Value::Struct(None, fields), extensions, visitor)
+            }
+            Value::NamedTuple(name, mut values) => {
+                let payload = match values.len() {
+                    1 => values.remove(0),
+                    _ => Value::Tuple(values),
+                };
+                visit_variant(name, payload, extensions, visitor)
+            }
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let Self { value, extensions } = self;
+
+        match value {
+            Value::Option(None) => visitor.visit_none(),
+            Value::Option(Some(value)) => {
+                visitor.visit_some(ValueDeserializer::new(*value, extensions))
+            }
+            value if extensions.implicit_some => {
+                visitor.visit_some(ValueDeserializer::new(value, extensions))
+            }
+            value => Err(value.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            // `()` is read as an empty tuple
+            Value::Unit => visitor.visit_unit(),
+            Value::Tuple(values) if values.is_empty() => visitor.visit_unit(),
+            value => Err(value.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match &self.value {
+            Value::UnitStruct(found) => {
+                check_name(Some(found), name, self.extensions)?;
+                visitor.visit_unit()
+            }
+            _ => {
+                check_name(None, name, self.extensions)?;
+                self.deserialize_unit(visitor)
+            }
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let Self { value, extensions } = self;
+
+        if extensions.unwrap_newtypes {
+            return visitor.visit_newtype_struct(ValueDeserializer::new(value, extensions));
+        }
+
+        let (found, mut values) = match value {
+            Value::Tuple(values) => (None, values),
+            Value::NamedTuple(found, values) => (Some(found), values),
+            value => return Err(value.invalid_type(&visitor)),
+        };
+
+        if values.len() != 1 {
+            return Err(de::Error::invalid_length(values.len(), &visitor));
+        }
+
+        check_name(found.as_deref(), name, extensions)?;
+        visitor.visit_newtype_struct(ValueDeserializer::new(values.remove(0), extensions))
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::List(values) => visit_seq(values, self.extensions, visitor),
+            value => Err(value.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Tuple(values) => visit_seq(values, self.extensions, visitor),
+            Value::Unit => visit_seq(Vec::new(), self.extensions, visitor),
+            value => Err(value.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::NamedTuple(found, values) => {
+                check_name(Some(&found), name, self.extensions)?;
+                visit_seq(values, self.extensions, visitor)
+            }
+            _ => {
+                check_name(None, name, self.extensions)?;
+                self.deserialize_tuple(len, visitor)
+            }
+        }
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::Map(map) => visit_map(map, self.extensions, visitor),
+            value => Err(value.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let extensions = self.extensions;
+
+        let (found, fields) = match self.value {
+            Value::Struct(found, fields) => (found, fields),
+            Value::UnitStruct(found) => (Some(found), Map::new()),
+            // a struct with only default fields
+            Value::Unit => (None, Map::new()),
+            Value::Tuple(values) if values.is_empty() => (None, Map::new()),
+            value => return Err(value.invalid_type(&visitor)),
+        };
+
+        check_name(found.as_deref(), name, extensions)?;
+        visit_fields(fields, extensions, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let (variant, payload) = match self.value {
+            Value::UnitStruct(variant) => (variant, Payload::Unit),
+            Value::NamedTuple(variant, values) => (variant, Payload::Tuple(values)),
+            Value::Struct(Some(variant), fields) => (variant, Payload::Struct(fields)),
+            value => return Err(value.invalid_type(&visitor)),
+        };
+
+        visitor.visit_enum(Enum {
+            variant,
+            payload,
+            extensions: self.extensions,
+        })
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Value::String(s) | Value::UnitStruct(s) => visitor.visit_string(s),
+            value => Err(value.invalid_type(&visitor)),
+        }
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf
+    }
+}
+
+impl IntoDeserializer<'_, ValueError> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        self
+    }
+}
+
+/// Call the method of [`ValueDeserializer`], without extensions
+macro_rules! forward_to_value_deserializer {
+    ($($method:ident($($arg:ident: $ty:ty),*))*) => {
+        $(
+            fn $method<V: Visitor<'de>>(
+                self,
+                $($arg: $ty,)*
+                visitor: V,
+            ) -> Result<V::Value, Self::Error> {
+                ValueDeserializer::new(self, Extensions::new()).$method($($arg,)* visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for Value {
+    type Error = ValueError;
+
+    forward_to_value_deserializer! {
+        deserialize_any()
+        deserialize_bool()
+        deserialize_i8()
+        deserialize_i16()
+        deserialize_i32()
+        deserialize_i64()
+        deserialize_i128()
+        deserialize_u8()
+        deserialize_u16()
+        deserialize_u32()
+        deserialize_u64()
+        deserialize_u128()
+        deserialize_f32()
+        deserialize_f64()
+        deserialize_char()
+        deserialize_str()
+        deserialize_string()
+        deserialize_bytes()
+        deserialize_byte_buf()
+        deserialize_option()
+        deserialize_unit()
+        deserialize_unit_struct(name: &'static str)
+        deserialize_newtype_struct(name: &'static str)
+        deserialize_seq()
+        deserialize_tuple(len: usize)
+        deserialize_tuple_struct(name: &'static str, len: usize)
+        deserialize_map()
+        deserialize_struct(name: &'static str, fields: &'static [&'static str])
+        deserialize_enum(name: &'static str, variants: &'static [&'static str])
+        deserialize_identifier()
+        deserialize_ignored_any()
+    }
+}
+
+impl IntoDeserializer<'_, ValueError> for Value {
+    type Deserializer = ValueDeserializer;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        ValueDeserializer::new(self, Extensions::new())
+    }
+}
+
+enum Payload {
+    Unit,
+    Tuple(Vec<Value>),
+    Struct(Map<String>),
+}
+
+struct Enum {
+    variant: String,
+    payload: Payload,
+    extensions: Extensions,
+}
+
+struct Variant {
+    payload: Payload,
+    extensions: Extensions,
+}
+
+impl<'de> EnumAccess<'de> for Enum {
+    type Error = ValueError;
+    type Variant = Variant;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, Self::Variant), Self::Error> {
+        let variant = seed.deserialize(self.variant.into_deserializer())?;
+        let variant_access = Variant {
+            payload: self.payload,
+            extensions: self.extensions,
+        };
+        Ok((variant, variant_access))
+    }
+}
+
+impl<'de> VariantAccess<'de> for Variant {
+    type Error = ValueError;
+
+    fn unit_variant(self) -> Result<(), Self::Error> {
+        match self.payload {
+            Payload::Unit => Ok(()),
+            _ => Err(de::Error::invalid_type(
+                Unexpected::Other("variant with fields"),
+                &"unit variant",
+            )),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, Self::Error> {
+        let unwrap = self.extensions.unwrap_variant_newtypes;
+
+        let value = match self.payload {
+            Payload::Tuple(mut values) if values.len() == 1 => values.remove(0),
+            // the struct or the tuple shares the parentheses of the variant
+            Payload::Tuple(values) if unwrap => Value::Tuple(values),
+            Payload::Struct(fields) if unwrap => Value::Struct(None, fields),
+            _ => {
+                return Err(de::Error::invalid_type(
+                    Unexpected::Other("variant"),
+                    &"newtype variant",
+                ));
+            }
+        };
+
+        seed.deserialize(ValueDeserializer::new(value, self.extensions))
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.payload {
+            Payload::Tuple(values) => visit_seq(values, self.extensions, visitor),
+            _ => Err(de::Error::invalid_type(
+                Unexpected::Other("variant"),
+                &"tuple variant",
+            )),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        match self.payload {
+            Payload::Struct(fields) => visit_fields(fields, self.extensions, visitor),
+            _ => Err(de::Error::invalid_type(
+                Unexpected::Other("variant"),
+                &"struct variant",
+            )),
+        }
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("any ron value")
+    }
+
+    fn visit_bool<E>(self, v: bool) -> Result<Value, E> {
+        Ok(Value::Bool(v))
+    }
+
+    fn visit_i64<E>(self, v: i64) -> Result<Value, E> {
+        Ok(Value::Number(Number::I64(v)))
+    }
+
+    fn visit_i128<E>(self, v: i128) -> Result<Value, E> {
+        Ok(Value::Number(Number::I128(v)))
+    }
+
+    fn visit_u64<E>(self, v: u64) -> Result<Value, E> {
+        Ok(Value::Number(Number::U64(v)))
+    }
+
+    fn visit_u128<E>(self, v: u128) -> Result<Value, E> {
+        Ok(Value::Number(Number::U128(v)))
+    }
+
+    fn visit_f32<E>(self, v: f32) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_f64<E>(self, v: f64) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_char<E>(self, v: char) -> Result<Value, E> {
+        Ok(Value::Char(v))
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Value, E> {
+        Ok(Value::from(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Value, E> {
+        Ok(Value::String(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Value, E> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Value, E> {
+        Ok(Value::Bytes(v))
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Option(None))
+    }
+
+    fn visit_some<D: de::Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        Ok(Value::Option(Some(Box::new(value))))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Unit)
+    }
+
+    fn visit_newtype_struct<D: de::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut values = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+        while let Some(value) = seq.next_element()? {
+            values.push(value);
+        }
+
+        Ok(Value::List(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut values = Map::new();
+
+        while let Some((key, value)) = map.next_entry::<Value, Value>()? {
+            values.insert(key, value);
+        }
+
+        Ok(Value::Map(values))
+    }
+}
+
+/// Enum variants and struct names can't be told apart from other values
+/// in the data model of serde: structs are read as maps, unit variants as
+/// strings, and variants with fields as a map from their name to the fields.
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: de::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
@@ -2,9 +2,18 @@ use std::borrow::Cow;
 
 // mod de;
 mod de;
+mod edit;
+mod error;
+mod extensions;
+mod fast_de;
+#[cfg(feature = "serde")]
+mod from_value;
 mod map;
 mod number;
 mod ser;
+mod spanned;
+#[cfg(feature = "serde")]
+mod to_value;
 
 #[cfg(test)]
 mod test;
@@ -12,9 +21,20 @@ mod test;
 pub use map::Map;
 pub use number::{F32, F64, Number};
 
-pub use de::from_str;
+pub use de::{Document, document_from_str, document_from_str_combinator, from_str};
 
-pub use ser::{SerializeError, to_string};
+pub use error::Error;
+
+pub use extensions::Extensions;
+
+pub use edit::{EditError, edit};
+
+pub use ser::{PrettyConfig, SerializeError, to_string, to_string_pretty};
+
+#[cfg(feature = "serde")]
+pub use from_value::{ValueDeserializer, ValueError, from_value, from_value_with_extensions};
+#[cfg(feature = "serde")]
+pub use to_value::{Serializer, to_string_pretty_typed, to_value};
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
 pub enum Value {
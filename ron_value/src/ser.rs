@@ -1,9 +1,12 @@
-use crate::{Number, Value};
+use crate::{Extensions, Number, Value};
 use std::fmt;
+use unicode_ident::{is_xid_continue, is_xid_start};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum SerializeError {
     UnsupportedVariant,
+    /// A field or struct name that can't be written, even as a raw identifier
+    InvalidIdentifier(String),
 }
 
 impl fmt::Display for SerializeError {
@@ -12,6 +15,9 @@ impl fmt::Display for SerializeError {
             SerializeError::UnsupportedVariant => {
                 write!(f, "unsupported variant for serialization")
             }
+            SerializeError::InvalidIdentifier(name) => {
+                write!(f, "{name:?} can't be written as an identifier")
+            }
         }
     }
 }
@@ -26,10 +32,7 @@ pub fn to_string(value: &Value) -> Result<String, SerializeError> {
         Value::Char(c) => Ok(format!("'{}'", escape_char(*c))),
         Value::Number(n) => Ok(number_to_string(n)),
         Value::String(s) => Ok(format!("\"{}\"", escape_string(s))),
-        Value::Bytes(b) => Ok(format!(
-            "b\"{}\"",
-            escape_string(&String::from_utf8_lossy(b))
-        )),
+        Value::Bytes(b) => Ok(format!("b\"{}\"", escape_bytes(b))),
         Value::Option(opt) => match opt {
             None => Ok("None".to_string()),
             Some(v) => Ok(format!("Some({})", to_string(v)?)),
@@ -49,14 +52,18 @@ pub fn to_string(value: &Value) -> Result<String, SerializeError> {
             let elems: Result<Vec<_>, _> = vec.iter().map(to_string).collect();
             Ok(format!("({})", elems?.join(", ")))
         }
-        Value::UnitStruct(name) => Ok(name.to_string()),
+        Value::UnitStruct(name) => ident_to_string(name),
         Value::Struct(opt_name, map) => {
             let elems: Result<Vec<_>, _> = map
                 .iter()
-                .map(|(k, v)| Ok(format!("{}: {}", k, to_string(v)?)))
+                .map(|(k, v)| Ok(format!("{}: {}", ident_to_string(k)?, to_string(v)?)))
                 .collect();
             if let Some(name) = opt_name {
-                Ok(format!("{} ( {} )", name, elems?.join(", ")))
+                Ok(format!(
+                    "{} ( {} )",
+                    ident_to_string(name)?,
+                    elems?.join(", ")
+                ))
             } else {
                 Ok(format!("( {} )", elems?.join(", ")))
             }
@@ -68,7 +75,7 @@ pub fn to_string(value: &Value) -> Result<String, SerializeError> {
                     Value::Struct(None, map) => {
                         let inner: Result<Vec<_>, _> = map
                             .iter()
-                            .map(|(k, v)| Ok(format!("{}: {}", k, to_string(v)?)))
+                            .map(|(k, v)| Ok(format!("{}: {}", ident_to_string(k)?, to_string(v)?)))
                             .collect();
                         Ok(format!("({})", inner?.join(",")))
                     }
@@ -76,7 +83,9 @@ pub fn to_string(value: &Value) -> Result<String, SerializeError> {
                         if let Value::Struct(None, map) = &inner_vec[0] {
                             let inner: Result<Vec<_>, _> = map
                                 .iter()
-                                .map(|(k, v)| Ok(format!("{}: {}", k, to_string(v)?)))
+                                .map(|(k, v)| {
+                                    Ok(format!("{}: {}", ident_to_string(k)?, to_string(v)?))
+                                })
                                 .collect();
                             Ok(format!("({})", inner?.join(",")))
                         } else {
@@ -87,36 +96,297 @@ pub fn to_string(value: &Value) -> Result<String, SerializeError> {
                 })
                 .collect();
 
-            Ok(format!("{}({})", name, elems?.join(", ")))
+            Ok(format!("{}({})", ident_to_string(name)?, elems?.join(", ")))
+        }
+    }
+}
+
+/// Options of [`to_string_pretty`], close to the `PrettyConfig` of ron
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PrettyConfig {
+    depth_limit: usize,
+    new_line: String,
+    indentor: String,
+    trailing_comma: bool,
+    struct_names: bool,
+    separate_tuple_members: bool,
+    extensions: Extensions,
+}
+
+impl Default for PrettyConfig {
+    fn default() -> Self {
+        Self {
+            depth_limit: usize::MAX,
+            new_line: "\n".to_string(),
+            indentor: "    ".to_string(),
+            trailing_comma: true,
+            struct_names: false,
+            separate_tuple_members: false,
+            extensions: Extensions::new(),
+        }
+    }
+}
+
+impl PrettyConfig {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Collections nested deeper than this are written on one line
+    #[must_use]
+    pub fn depth_limit(mut self, depth_limit: usize) -> Self {
+        self.depth_limit = depth_limit;
+        self
+    }
+
+    #[must_use]
+    pub fn new_line(mut self, new_line: impl Into<String>) -> Self {
+        self.new_line = new_line.into();
+        self
+    }
+
+    #[must_use]
+    pub fn indentor(mut self, indentor: impl Into<String>) -> Self {
+        self.indentor = indentor.into();
+        self
+    }
+
+    /// Write a comma after the last element of the collections written on several lines
+    #[must_use]
+    pub fn trailing_comma(mut self, trailing_comma: bool) -> Self {
+        self.trailing_comma = trailing_comma;
+        self
+    }
+
+    /// Write the names of structs, when serializing a type.
+    ///
+    /// A [`Value`] keeps the names it has: they can't be told apart from
+    /// the names of enum variants.
+    #[must_use]
+    pub fn struct_names(mut self, struct_names: bool) -> Self {
+        self.struct_names = struct_names;
+        self
+    }
+
+    pub fn get_struct_names(&self) -> bool {
+        self.struct_names
+    }
+
+    /// Write each member of tuples on its own line, like the elements of lists
+    #[must_use]
+    pub fn separate_tuple_members(mut self, separate_tuple_members: bool) -> Self {
+        self.separate_tuple_members = separate_tuple_members;
+        self
+    }
+
+    /// Write an attribute for each extension at the start of the file.
+    ///
+    /// When serializing a type, the value is also written with them,
+    /// like omitting `Some`.
+    #[must_use]
+    pub fn extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    pub fn get_extensions(&self) -> Extensions {
+        self.extensions
+    }
+}
+
+/// Write `value` on several lines, indented
+pub fn to_string_pretty(value: &Value, config: &PrettyConfig) -> Result<String, SerializeError> {
+    let mut printer = Printer {
+        config,
+        output: String::new(),
+        depth: 0,
+    };
+
+    for name in config.extensions.names() {
+        printer.output.push_str(&format!("#![enable({name})]"));
+        printer.output.push_str(&config.new_line);
+    }
+
+    printer.value(value)?;
+
+    Ok(printer.output)
+}
+
+struct Printer<'a> {
+    config: &'a PrettyConfig,
+    output: String,
+    /// Number of collections we are in
+    depth: usize,
+}
+
+impl Printer<'_> {
+    fn value(&mut self, value: &Value) -> Result<(), SerializeError> {
+        match value {
+            Value::Option(Some(value)) => {
+                self.output.push_str("Some(");
+                self.value(value)?;
+                self.output.push(')');
+            }
+            Value::List(values) => {
+                self.collection("[", "]", true, values, |p, value| p.value(value))?
+            }
+            Value::Map(map) => self.collection("{", "}", true, map.iter(), |p, (key, value)| {
+                p.output.push_str(&to_string(key)?);
+                p.output.push_str(": ");
+                p.value(value)
+            })?,
+            Value::Tuple(values) => self.tuple(values)?,
+            Value::NamedTuple(name, values) => {
+                self.output.push_str(&ident_to_string(name)?);
+                self.tuple(values)?;
+            }
+            Value::Struct(name, fields) => {
+                if let Some(name) = name {
+                    self.output.push_str(&ident_to_string(name)?);
+                }
+                self.collection("(", ")", true, fields.iter(), |p, (key, value)| {
+                    p.output.push_str(&ident_to_string(key)?);
+                    p.output.push_str(": ");
+                    p.value(value)
+                })?
+            }
+            value => self.output.push_str(&to_string(value)?),
+        }
+
+        Ok(())
+    }
+
+    fn tuple(&mut self, values: &[Value]) -> Result<(), SerializeError> {
+        // a single member is a newtype, which ron keeps inline
+        let separate = self.config.separate_tuple_members && values.len() > 1;
+        self.collection("(", ")", separate, values, |p, value| p.value(value))
+    }
+
+    /// Write the elements between `open` and `close`, on several lines if
+    /// `nested` and the depth limit allows it, or separated by `, `.
+    /// Like ron, only the collections written on several lines are a level of nesting.
+    fn collection<T>(
+        &mut self,
+        open: &str,
+        close: &str,
+        nested: bool,
+        elements: impl IntoIterator<Item = T>,
+        mut element: impl FnMut(&mut Self, T) -> Result<(), SerializeError>,
+    ) -> Result<(), SerializeError> {
+        let mut elements = elements.into_iter().peekable();
+
+        self.output.push_str(open);
+
+        if elements.peek().is_none() {
+            self.output.push_str(close);
+            return Ok(());
+        }
+
+        if nested {
+            self.depth += 1;
+        }
+
+        let multiline = nested && self.depth <= self.config.depth_limit;
+
+        while let Some(value) = elements.next() {
+            let last = elements.peek().is_none();
+
+            if multiline {
+                self.new_line();
+                element(self, value)?;
+
+                if !last || self.config.trailing_comma {
+                    self.output.push(',');
+                }
+            } else {
+                element(self, value)?;
+
+                if !last {
+                    self.output.push_str(", ");
+                }
+            }
+        }
+
+        if nested {
+            self.depth -= 1;
+        }
+
+        if multiline {
+            self.new_line();
+        }
+
+        self.output.push_str(close);
+
+        Ok(())
+    }
+
+    fn new_line(&mut self) {
+        self.output.push_str(&self.config.new_line);
+
+        for _ in 0..self.depth {
+            self.output.push_str(&self.config.indentor);
         }
     }
 }
 
+/// Words which can't be used as an identifier in Rust without `r#`
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "super", "trait", "true", "type", "unsafe", "use",
+    "where", "while", "abstract", "become", "box", "do", "final", "gen", "macro", "override",
+    "priv", "try", "typeof", "unsized", "virtual", "yield",
+];
+
+/// Write a field or struct name, as a raw identifier (`r#type`) when it is
+/// a keyword or has chars only allowed in raw identifiers, like `-`
+pub(crate) fn ident_to_string(name: &str) -> Result<String, SerializeError> {
+    let mut chars = name.chars();
+
+    let is_std = chars
+        .next()
+        .is_some_and(|first| is_xid_start(first) || first == '_')
+        && chars.all(is_xid_continue);
+
+    if is_std && !KEYWORDS.contains(&name) {
+        return Ok(name.to_string());
+    }
+
+    let is_raw = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| is_xid_continue(c) || matches!(c, '.' | '+' | '-'));
+
+    if is_raw {
+        Ok(format!("r#{name}"))
+    } else {
+        Err(SerializeError::InvalidIdentifier(name.to_string()))
+    }
+}
+
+/// Like ron, which also escapes the control and the non printable chars
 fn escape_string(s: &str) -> String {
-    s.chars()
-        .flat_map(|c| match c {
-            '\\' => "\\\\".chars().collect::<Vec<_>>(),
-            '"' => "\\\"".chars().collect::<Vec<_>>(),
-            '\n' => "\\n".chars().collect::<Vec<_>>(),
-            '\r' => "\\r".chars().collect::<Vec<_>>(),
-            '\t' => "\\t".chars().collect::<Vec<_>>(),
-            other => vec![other],
-        })
+    s.chars().flat_map(char::escape_debug).collect()
+}
+
+/// Like ron, with `\xNN` for the non printable bytes
+fn escape_bytes(b: &[u8]) -> String {
+    b.iter()
+        .flat_map(|b| std::ascii::escape_default(*b))
+        .map(char::from)
         .collect()
 }
 
+/// Like ron, the other chars are written as they are
 fn escape_char(c: char) -> String {
     match c {
         '\\' => "\\\\".to_string(),
         '\'' => "\\'".to_string(),
-        '\n' => "\\n".to_string(),
-        '\r' => "\\r".to_string(),
-        '\t' => "\\t".to_string(),
         other => other.to_string(),
     }
 }
 
-fn number_to_string(n: &Number) -> String {
+pub(crate) fn number_to_string(n: &Number) -> String {
     match n {
         Number::I8(v) => v.to_string(),
         Number::I16(v) => v.to_string(),
@@ -128,7 +398,15 @@ fn number_to_string(n: &Number) -> String {
         Number::U32(v) => v.to_string(),
         Number::U64(v) => v.to_string(),
         Number::U128(v) => v.to_string(),
-        Number::F32(v) => format!("{}", v.get()),
-        Number::F64(v) => format!("{}", v.get()),
+        Number::F32(v) => float_to_string(v.get().to_string()),
+        Number::F64(v) => float_to_string(v.get().to_string()),
+    }
+}
+
+/// Whole floats keep a fractional part, like ron, so they are read back as floats
+fn float_to_string(mut s: String) -> String {
+    if s.bytes().all(|b| b == b'-' || b.is_ascii_digit()) {
+        s.push_str(".0");
     }
+    s
 }
@@ -0,0 +1,194 @@
+//! A parse which keeps the position of each value in the source,
+//! following the same grammar as [`crate::from_str`].
+
+use std::ops::Range;
+
+use pom::parser::*;
+
+use crate::{
+    Map, Value,
+    de::{
+        bool, byte, byte_string, char, comma, end_of_file, extensions, float, ident, integer,
+        keyword, string, token, ws,
+    },
+    error::{self, Error, expected},
+};
+
+/// A value with its position in the source, in chars
+#[derive(Debug)]
+pub(crate) struct Spanned {
+    pub span: Range<usize>,
+    pub kind: SpannedKind,
+}
+
+#[derive(Debug)]
+pub(crate) enum SpannedKind {
+    /// A value without children
+    Scalar(Value),
+    /// `Some(..)`
+    Option(Box<Spanned>),
+    List(Vec<Spanned>),
+    Map(Vec<Entry<Spanned>>),
+    /// Tuple and named tuple
+    Tuple(Option<String>, Vec<Spanned>),
+    Struct(Option<String>, Vec<Entry<String>>),
+}
+
+/// A map entry or a struct field
+#[derive(Debug)]
+pub(crate) struct Entry<K> {
+    /// From the start of the key to the end of the value
+    pub span: Range<usize>,
+    pub key: K,
+    pub value: Spanned,
+}
+
+impl Spanned {
+    pub fn to_value(&self) -> Value {
+        match &self.kind {
+            SpannedKind::Scalar(value) => value.clone(),
+            SpannedKind::Option(value) => Value::Option(Some(Box::new(value.to_value()))),
+            SpannedKind::List(values) => {
+                Value::List(values.iter().map(Spanned::to_value).collect())
+            }
+            SpannedKind::Map(entries) => {
+                let mut map = Map::new();
+
+                for entry in entries {
+                    map.insert(entry.key.to_value(), entry.value.to_value());
+                }
+
+                Value::Map(map)
+            }
+            SpannedKind::Tuple(name, values) => {
+                let values = values.iter().map(Spanned::to_value).collect();
+
+                match name {
+                    Some(name) => Value::NamedTuple(name.clone(), values),
+                    None => Value::Tuple(values),
+                }
+            }
+            SpannedKind::Struct(name, fields) => {
+                let mut map = Map::new();
+
+                for field in fields {
+                    map.insert(field.key.clone(), field.value.to_value());
+                }
+
+                Value::Struct(name.clone(), map)
+            }
+        }
+    }
+}
+
+pub(crate) fn from_chars(input: &[char]) -> Result<Spanned, Error> {
+    error::parse(ws() * extensions() * value() - ws() - end_of_file(), input)
+}
+
+fn value<'a>() -> Parser<'a, char, Spanned> {
+    (empty().pos() + kind() + empty().pos()).map(|((start, kind), end)| Spanned {
+        span: start..end,
+        kind,
+    })
+}
+
+fn kind<'a>() -> Parser<'a, char, SpannedKind> {
+    let kind = scalar().map(SpannedKind::Scalar)
+        | option_some().map(|value| SpannedKind::Option(Box::new(value)))
+        | list().map(SpannedKind::List)
+        | map().map(SpannedKind::Map)
+        | tuple().map(|values| SpannedKind::Tuple(None, values))
+        | struct_();
+
+    expected(kind, "a value")
+}
+
+fn scalar<'a>() -> Parser<'a, char, Value> {
+    integer().map(Value::Number)
+        | float().map(Value::Number)
+        | byte().map(|b| Value::Bytes(vec![b]))
+        | string().map(Value::String)
+        | byte_string().map(Value::Bytes)
+        | char().map(Value::Char)
+        | bool().map(Value::Bool)
+        | keyword(&['N', 'o', 'n', 'e']).map(|_| Value::Option(None))
+}
+
+fn option_some<'a>() -> Parser<'a, char, Spanned> {
+    seq(&['S', 'o', 'm', 'e']) * ws() * token('(', "`(`") * ws() * call(value)
+        - ws()
+        - token(')', "`)`")
+}
+
+fn list<'a>() -> Parser<'a, char, Vec<Spanned>> {
+    (sym('[') * ws() * (call(value) + (comma() * call(value)).repeat(0..) - comma().opt()).opt()
+        - ws()
+        - token(']', "`]`"))
+    .map(first_and_rest)
+}
+
+fn map<'a>() -> Parser<'a, char, Vec<Entry<Spanned>>> {
+    (sym('{') * ws() * (map_entry() + (comma() * map_entry()).repeat(0..) - comma().opt()).opt()
+        - ws()
+        - token('}', "`}`"))
+    .map(first_and_rest)
+}
+
+fn map_entry<'a>() -> Parser<'a, char, Entry<Spanned>> {
+    (empty().pos() + call(value) - ws() - token(':', "`:`") - ws() + call(value) + empty().pos())
+        .map(|(((start, key), value), end)| Entry {
+            span: start..end,
+            key,
+            value,
+        })
+}
+
+fn tuple<'a>() -> Parser<'a, char, Vec<Spanned>> {
+    (sym('(') * ws() * (call(value) + (comma() * call(value)).repeat(0..) - comma().opt()).opt()
+        - ws()
+        - token(')', "`)`"))
+    .map(first_and_rest)
+}
+
+fn struct_<'a>() -> Parser<'a, char, SpannedKind> {
+    tuple_struct() | named_struct() | unit_struct()
+}
+
+fn unit_struct<'a>() -> Parser<'a, char, SpannedKind> {
+    seq(&['(', ')']).map(|_| SpannedKind::Scalar(Value::Unit))
+        | ident().map(|name| SpannedKind::Scalar(Value::UnitStruct(name)))
+}
+
+fn tuple_struct<'a>() -> Parser<'a, char, SpannedKind> {
+    (ident().opt() - ws() + tuple()).map(|(name, values)| SpannedKind::Tuple(name, values))
+}
+
+fn named_struct<'a>() -> Parser<'a, char, SpannedKind> {
+    (ident().opt() - ws() - sym('(') - ws()
+        + (named_field() + (comma() * named_field()).repeat(0..) - comma().opt()).opt()
+        - ws()
+        - token(')', "`)`"))
+    .map(|(name, fields)| SpannedKind::Struct(name, first_and_rest(fields)))
+}
+
+fn named_field<'a>() -> Parser<'a, char, Entry<String>> {
+    (empty().pos() + expected(ident(), "a field name") - ws() - token(':', "`:`") - ws()
+        + call(value)
+        + empty().pos())
+    .map(|(((start, key), value), end)| Entry {
+        span: start..end,
+        key,
+        value,
+    })
+}
+
+fn first_and_rest<T>(v: Option<(T, Vec<T>)>) -> Vec<T> {
+    let mut vec = Vec::new();
+
+    if let Some((first, rest)) = v {
+        vec.push(first);
+        vec.extend(rest);
+    }
+
+    vec
+}
@@ -2,7 +2,7 @@ use std::fmt::Debug;
 
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
 
-use crate::{F32, F64, Number, Value};
+use crate::{Extensions, F32, F64, Number, Value};
 
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct Complex {
@@ -81,6 +81,33 @@ fn float_frac() {
     assert_eq!(v, Value::Number(Number::F64(F64(0.1))));
 }
 
+#[test]
+fn float_exp() {
+    for (input, expected) in [
+        ("1e5", 1e5),
+        ("1E+5", 1e5),
+        ("-2e-3", -2e-3),
+        // too large for a f64, like in ron
+        ("1e400", f64::INFINITY),
+    ] {
+        let expected = Some(Value::Number(Number::F64(F64(expected))));
+
+        assert_eq!(crate::from_str(input).ok(), expected, "{input}");
+        assert_eq!(
+            crate::document_from_str(input).ok().map(|d| d.value),
+            expected,
+            "{input}"
+        );
+        assert_eq!(
+            crate::document_from_str_combinator(input)
+                .ok()
+                .map(|d| d.value),
+            expected,
+            "{input}"
+        );
+    }
+}
+
 #[test]
 fn integer() {
     let v = crate::from_str("1").unwrap();
@@ -115,3 +142,499 @@ fn list() {
         ])
     );
 }
+
+const COMMENTED: &str = "// panel config
+(
+    // the name of the panel
+    name: \"Panel\",
+    size: 1_6,
+    anchor: Top, /* can be Bottom */
+    list: [1, 2],
+)
+";
+
+#[test]
+fn edit_unchanged() {
+    let value = crate::from_str(COMMENTED).unwrap();
+
+    assert_eq!(crate::edit(COMMENTED, &value).unwrap(), COMMENTED);
+}
+
+#[test]
+fn edit_number_kind() {
+    let mut value = crate::from_str(COMMENTED).unwrap();
+
+    // the same number, read with the type of the schema
+    if let Value::Struct(_, map) = &mut value {
+        map.insert("size", Value::Number(Number::U32(16)));
+    }
+
+    assert_eq!(crate::edit(COMMENTED, &value).unwrap(), COMMENTED);
+}
+
+#[test]
+fn edit_keep_comments() {
+    let mut value = crate::from_str(COMMENTED).unwrap();
+
+    if let Value::Struct(_, map) = &mut value {
+        map.insert("name", Value::from("Dock"));
+        map.insert("list", Value::List(vec![Value::from(1), Value::from(3)]));
+    }
+
+    assert_eq!(
+        crate::edit(COMMENTED, &value).unwrap(),
+        COMMENTED
+            .replace("\"Panel\"", "\"Dock\"")
+            .replace("[1, 2]", "[1, 3]")
+    );
+}
+
+#[test]
+fn edit_add_and_remove_fields() {
+    let mut value = crate::from_str(COMMENTED).unwrap();
+
+    if let Value::Struct(_, map) = &mut value {
+        map.remove(&"anchor".to_string());
+        map.insert("opacity", Value::from(0.5));
+    }
+
+    let edited = crate::edit(COMMENTED, &value).unwrap();
+
+    assert_eq!(
+        edited,
+        "// panel config
+(
+    // the name of the panel
+    name: \"Panel\",
+    size: 1_6, /* can be Bottom */
+    list: [1, 2],
+    opacity: 0.5,
+)
+"
+    );
+    assert_eq!(crate::from_str(&edited).unwrap(), value);
+}
+
+#[test]
+fn edit_replace_list() {
+    let source = "[1, /* two */ 2]";
+
+    let edited = crate::edit(source, &Value::List(vec![Value::from(1)])).unwrap();
+
+    assert_eq!(edited, "[1]");
+}
+
+#[test]
+fn edit_add_nested_fields() {
+    let source = "(
+	name: \"Panel\",
+	inner: (
+		a: 1,
+	),
+)
+";
+    let mut value = crate::from_str(source).unwrap();
+
+    if let Value::Struct(_, map) = &mut value {
+        map.insert("type", crate::from_str("1").unwrap());
+        map.insert("list", crate::from_str("[1, 2]").unwrap());
+        if let Some(Value::Struct(_, inner)) = map.get_mut(&"inner".to_string()) {
+            inner.insert("b", crate::from_str("(c: true)").unwrap());
+        }
+    }
+
+    let edited = crate::edit(source, &value).unwrap();
+
+    // the added entries follow the indentation of the file
+    assert_eq!(
+        edited,
+        "(
+	name: \"Panel\",
+	inner: (
+		a: 1,
+		b: (
+			c: true,
+		),
+	),
+	r#type: 1,
+	list: [
+		1,
+		2,
+	],
+)
+"
+    );
+    assert_eq!(crate::from_str(&edited).unwrap(), value);
+
+    // an inline struct stays inline
+    let edited = crate::edit("(a: 1)", &crate::from_str("(a: 1, b: (c: 2))").unwrap()).unwrap();
+    assert_eq!(edited, "(a: 1, b: ( c: 2 ))");
+}
+
+#[test]
+fn raw_identifiers() {
+    let value = crate::from_str("(r#type: 1, r#a-b: 2, c: 3)").unwrap();
+
+    assert_eq!(
+        crate::to_string(&value).unwrap(),
+        "( r#type: 1, r#a-b: 2, c: 3 )"
+    );
+    assert_eq!(
+        crate::to_string_pretty(&value, &crate::PrettyConfig::new()).unwrap(),
+        "(\n    r#type: 1,\n    r#a-b: 2,\n    c: 3,\n)"
+    );
+
+    let mut invalid = crate::Map::new();
+    invalid.insert("a b", Value::from(1));
+    assert!(crate::to_string(&Value::Struct(None, invalid)).is_err());
+}
+
+#[test]
+fn error_position() {
+    let e = crate::from_str("(a: 1) x").unwrap_err();
+
+    assert_eq!(e.offset(), 7);
+    assert_eq!(e.expected(), ["the end of the file"]);
+
+    let e = crate::from_str("(\n    a: 1,\n    size: 1 b: 2,\n)").unwrap_err();
+
+    assert_eq!((e.line(), e.column()), (3, 13));
+    assert_eq!(e.found(), "`b`");
+    assert_eq!(e.expected(), ["`,`", "`)`"]);
+    assert_eq!(e.message(), "expected `,` or `)`, found `b`");
+    assert_eq!(e.snippet(), "  |\n3 |     size: 1 b: 2,\n  |             ^");
+
+    let e = crate::edit("[1, 2", &Value::Unit).unwrap_err();
+
+    assert!(matches!(e, crate::EditError::Parse(e) if e.column() == 6));
+}
+
+#[test]
+fn invalid_numbers_and_escapes() {
+    for input in [
+        "(a: 99999999999999999999999999999999999999999,,)",
+        "0xfffffffffffffffffffffffffffffffff",
+        "'\\u{d800}'",
+        "\"\\u{110000}\"",
+    ] {
+        assert!(crate::from_str(input).is_err(), "{input}");
+        assert!(
+            crate::document_from_str_combinator(input).is_err(),
+            "{input}"
+        );
+        assert!(crate::edit(input, &Value::Unit).is_err(), "{input}");
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Typed {
+    unit: (),
+    option: Option<Complex>,
+    none: Option<i32>,
+    list: Vec<EnumComplex>,
+    map: std::collections::BTreeMap<String, u8>,
+    tuple: (i64, char),
+    newtype: Newtype,
+    float: f32,
+    whole: (f32, f64),
+    text: String,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct Newtype(u16);
+
+/// `T` -> `Value` -> `T`, and through the ron text of both crates
+fn roundtrip_value<T>(data: T)
+where
+    T: Serialize + DeserializeOwned + Debug + PartialEq,
+{
+    let value = crate::to_value(&data).unwrap();
+    assert_eq!(crate::from_value::<T>(value.clone()).unwrap(), data);
+
+    let str_from_value = crate::to_string(&value).unwrap();
+    assert_eq!(ron::from_str::<T>(&str_from_value).unwrap(), data);
+
+    let str_from_serde = ron::ser::to_string(&data).unwrap();
+    let parsed = crate::from_str(&str_from_serde).unwrap();
+    assert_eq!(crate::from_value::<T>(parsed).unwrap(), data);
+}
+
+#[test]
+fn value_roundtrip() {
+    roundtrip_value(Typed {
+        unit: (),
+        option: Some(Complex {
+            x: "hello".into(),
+            y: -1,
+        }),
+        none: None,
+        list: vec![
+            EnumComplex::A,
+            EnumComplex::B(2),
+            EnumComplex::C(Complex {
+                x: "c".into(),
+                y: 3,
+            }),
+            EnumComplex::D {
+                a: 4,
+                b: Complex {
+                    x: "d".into(),
+                    y: 5,
+                },
+            },
+        ],
+        map: [("a".to_string(), 1), ("b".to_string(), 2)].into(),
+        tuple: (-6, 'x'),
+        newtype: Newtype(7),
+        float: 0.5,
+        whole: (2.0, 1.0),
+        text: "a\u{1}b".into(),
+    });
+}
+
+#[test]
+fn from_value_ron_rules() {
+    // options must be explicit
+    let value = crate::from_str("(x: \"a\", y: 1)").unwrap();
+    assert!(crate::from_value::<Option<Complex>>(value.clone()).is_err());
+    assert!(crate::from_value::<Complex>(value).is_ok());
+
+    // struct names are optional, but checked
+    let value = crate::from_str("Complex(x: \"a\", y: 1)").unwrap();
+    assert!(crate::from_value::<Complex>(value).is_ok());
+    let value = crate::from_str("Other(x: \"a\", y: 1)").unwrap();
+    assert!(crate::from_value::<Complex>(value).is_err());
+
+    let value = crate::from_str("E(1)").unwrap();
+    assert!(crate::from_value::<EnumComplex>(value).is_err());
+}
+
+#[test]
+fn value_serde() {
+    let value = crate::from_str("(a: Some(1), b: [B(2), C(3, 4)])").unwrap();
+
+    let parsed: Value = crate::from_value(value).unwrap();
+    assert_eq!(
+        crate::to_string(&parsed).unwrap(),
+        "{\"a\": Some(1), \"b\": [{\"B\": 2}, {\"C\": [3, 4]}]}"
+    );
+
+    // the other way, through serde's representation of enums
+    assert_eq!(crate::to_value(&parsed).unwrap(), parsed);
+}
+
+fn typed() -> Typed {
+    Typed {
+        unit: (),
+        option: Some(Complex {
+            x: "hello".into(),
+            y: -1,
+        }),
+        none: None,
+        list: vec![
+            EnumComplex::A,
+            EnumComplex::B(2),
+            EnumComplex::C(Complex {
+                x: "c".into(),
+                y: 3,
+            }),
+            EnumComplex::D {
+                a: 4,
+                b: Complex {
+                    x: "d".into(),
+                    y: 5,
+                },
+            },
+        ],
+        map: [("a".to_string(), 1), ("b".to_string(), 2)].into(),
+        tuple: (-6, 'x'),
+        newtype: Newtype(7),
+        float: 0.5,
+        whole: (2.0, 1.0),
+        text: "a\u{1}b".into(),
+    }
+}
+
+#[test]
+fn pretty_like_ron() {
+    use ron::ser::PrettyConfig as RonConfig;
+
+    let configs = [
+        (crate::PrettyConfig::new(), RonConfig::new()),
+        (
+            crate::PrettyConfig::new().depth_limit(2).indentor("\t"),
+            RonConfig::new().depth_limit(2).indentor("\t"),
+        ),
+        (
+            crate::PrettyConfig::new()
+                .struct_names(true)
+                .separate_tuple_members(true),
+            RonConfig::new()
+                .struct_names(true)
+                .separate_tuple_members(true),
+        ),
+        (
+            crate::PrettyConfig::new().extensions(Extensions {
+                unwrap_newtypes: true,
+                implicit_some: true,
+                unwrap_variant_newtypes: true,
+                explicit_struct_names: false,
+            }),
+            RonConfig::new().extensions(
+                ron::extensions::Extensions::UNWRAP_NEWTYPES
+                    | ron::extensions::Extensions::IMPLICIT_SOME
+                    | ron::extensions::Extensions::UNWRAP_VARIANT_NEWTYPES,
+            ),
+        ),
+    ];
+
+    for (config, ron_config) in configs {
+        let pretty = crate::to_string_pretty_typed(&typed(), &config).unwrap();
+        assert_eq!(
+            pretty,
+            ron::ser::to_string_pretty(&typed(), ron_config).unwrap()
+        );
+        assert_eq!(ron::from_str::<Typed>(&pretty).unwrap(), typed());
+
+        let document = crate::document_from_str(&pretty).unwrap();
+        assert_eq!(document.extensions, config.get_extensions());
+        assert_eq!(
+            crate::from_value_with_extensions::<Typed>(document.value, document.extensions)
+                .unwrap(),
+            typed()
+        );
+    }
+}
+
+#[test]
+fn extensions_attributes() {
+    let source = "#![enable(implicit_some)]\n#![enable(unwrap_newtypes, explicit_struct_names)]\nComplex(x: r#\"a \"b\"\"#, y: 1)";
+
+    let document = crate::document_from_str(source).unwrap();
+    assert_eq!(
+        document.extensions.names().collect::<Vec<_>>(),
+        ["unwrap_newtypes", "implicit_some", "explicit_struct_names"]
+    );
+    assert_eq!(crate::from_str(source).unwrap(), document.value);
+
+    // implicit `Some`, and a struct name is required
+    let complex: Option<Complex> =
+        crate::from_value_with_extensions(document.value, document.extensions).unwrap();
+    assert_eq!(complex.unwrap().x, "a \"b\"");
+
+    let value = crate::from_str("(x: \"a\", y: 1)").unwrap();
+    assert!(crate::from_value_with_extensions::<Complex>(value, document.extensions).is_err());
+
+    // the header is kept when editing
+    let edited = crate::edit(source, &crate::from_str("Complex(x: \"a\", y: 2)").unwrap());
+    assert_eq!(
+        edited.unwrap(),
+        "#![enable(implicit_some)]\n#![enable(unwrap_newtypes, explicit_struct_names)]\nComplex(x: \"a\", y: 2)"
+    );
+
+    let e = crate::from_str("#![enable(implicit_none)] 1").unwrap_err();
+    assert_eq!(
+        e.message(),
+        "expected an extension or `)`, found `implicit_none`"
+    );
+
+    assert!(crate::from_str("r#\"not terminated\"").is_err());
+}
+
+#[test]
+fn pretty_options() {
+    let value = crate::from_str("(a: [1, 2], b: {\"k\": (1, 2)})").unwrap();
+
+    let config = crate::PrettyConfig::new()
+        .new_line("\r\n")
+        .indentor("  ")
+        .trailing_comma(false)
+        .depth_limit(1);
+
+    assert_eq!(
+        crate::to_string_pretty(&value, &config).unwrap(),
+        "(\r\n  a: [1, 2],\r\n  b: {\"k\": (1, 2)}\r\n)"
+    );
+}
+
+#[test]
+fn fast_parser_like_combinator() {
+    let pretty = crate::to_string_pretty_typed(&typed(), &crate::PrettyConfig::new()).unwrap();
+
+    let sources = [
+        COMMENTED,
+        &pretty,
+        "()",
+        "( )",
+        "Some(1, 2)",
+        "Some ( Some(()) )",
+        "Some",
+        "None",
+        "-5u8",
+        "1_000i16",
+        "-170141183460469231731687303715884105728",
+        "1.",
+        ".5",
+        "-inf",
+        "1.5f32",
+        "b'a'",
+        "b\"a\\x41\\n\"",
+        "br#\"a\"#",
+        "r##\"a\"#b\"##",
+        "'\\''",
+        "'é'",
+        "\"a\\u{41}\\n\\\"\"",
+        "(a: 1, b: [1, 2,], c: {\"k\": (1,)},)",
+        "Foo(a: Some(Bar(1)), b: Baz, c: Qux())",
+        "(true, false, b)",
+        "/* a /* nested */ */ 1 // end\n",
+        "#![enable(implicit_some)]\n#![enable()] 1",
+        "\u{2028}[\u{200E}é, _a]",
+        "1 // c",
+        "truex",
+        "Nonex",
+        "infinity",
+        "1.5e-3f32",
+        "1.5E+_1_0",
+        "1e5",
+        "1E+5",
+        "r#A",
+        "(r#a: 1)",
+        "r#true",
+    ];
+
+    for source in sources {
+        let document = crate::fast_de::parse(source);
+        assert!(document.is_some(), "{source}");
+        assert_eq!(
+            document,
+            crate::document_from_str_combinator(source).ok(),
+            "{source}"
+        );
+
+        // used by edit
+        let chars = source.chars().collect::<Vec<_>>();
+        assert_eq!(
+            crate::spanned::from_chars(&chars)
+                .ok()
+                .map(|spanned| spanned.to_value()),
+            document.map(|document| document.value),
+            "{source}"
+        );
+    }
+
+    // rejected by both
+    for source in [
+        "(a: 1 b: 2)",
+        "[1, 2",
+        "\"a",
+        "1 2",
+        "/* a",
+        "#![enable(a)] 1",
+        "\"\\u41\"",
+        "1e5u8",
+    ] {
+        assert_eq!(crate::fast_de::parse(source), None, "{source}");
+        assert!(crate::from_str(source).is_err());
+    }
+}
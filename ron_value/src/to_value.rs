@@ -0,0 +1,543 @@
+//! Serialize any type to a [`Value`], the way ron writes it:
+//! struct names are optional, enum variants are named.
+
+use serde::{Serialize, ser};
+
+use crate::{Extensions, Map, Number, PrettyConfig, Value, ValueError, to_string_pretty};
+
+/// Serialize a `T` to a [`Value`]
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, ValueError> {
+    value.serialize(Serializer::new())
+}
+
+/// Serialize a `T` to ron, on several lines
+pub fn to_string_pretty_typed<T: Serialize + ?Sized>(
+    value: &T,
+    config: &PrettyConfig,
+) -> Result<String, ValueError> {
+    let serializer = Serializer::new()
+        .struct_names(config.get_struct_names())
+        .extensions(config.get_extensions());
+
+    let value = value.serialize(serializer)?;
+
+    to_string_pretty(&value, config).map_err(ser::Error::custom)
+}
+
+/// A [`serde::Serializer`] which produces a [`Value`]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Serializer {
+    struct_names: bool,
+    extensions: Extensions,
+}
+
+impl Serializer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Keep the names of structs, which are not needed to read them back
+    #[must_use]
+    pub fn struct_names(mut self, struct_names: bool) -> Self {
+        self.struct_names = struct_names;
+        self
+    }
+
+    /// Write the values like a file which enables these extensions, like omitting `Some`
+    #[must_use]
+    pub fn extensions(mut self, extensions: Extensions) -> Self {
+        self.extensions = extensions;
+        self
+    }
+
+    fn name(self, name: &'static str) -> Option<String> {
+        (self.struct_names || self.extensions.explicit_struct_names).then(|| name.to_string())
+    }
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Value;
+    type Error = ValueError;
+
+    type SerializeSeq = SerializeSeq;
+    type SerializeTuple = SerializeSeq;
+    type SerializeTupleStruct = SerializeSeq;
+    type SerializeTupleVariant = SerializeSeq;
+    type SerializeMap = SerializeMap;
+    type SerializeStruct = SerializeStruct;
+    type SerializeStructVariant = SerializeStruct;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, ValueError> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i128(self, v: i128) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u128(self, v: u128) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, ValueError> {
+        Ok(Value::Char(v))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, ValueError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, ValueError> {
+        Ok(Value::Bytes(v.to_vec()))
+    }
+
+    fn serialize_none(self) -> Result<Value, ValueError> {
+        Ok(Value::Option(None))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, ValueError> {
+        let value = value.serialize(self)?;
+
+        match value {
+            // `Some(None)` would be read as `None`
+            Value::Option(_) => Ok(Value::Option(Some(Box::new(value)))),
+            value if self.extensions.implicit_some => Ok(value),
+            value => Ok(Value::Option(Some(Box::new(value)))),
+        }
+    }
+
+    fn serialize_unit(self) -> Result<Value, ValueError> {
+        Ok(Value::Unit)
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<Value, ValueError> {
+        match self.name(name) {
+            Some(name) => Ok(Value::UnitStruct(name)),
+            None => Ok(Value::Unit),
+        }
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Value, ValueError> {
+        Ok(Value::UnitStruct(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<Value, ValueError> {
+        if self.extensions.unwrap_newtypes {
+            return value.serialize(self);
+        }
+
+        let values = vec![value.serialize(self)?];
+
+        match self.name(name) {
+            Some(name) => Ok(Value::NamedTuple(name, values)),
+            None => Ok(Value::Tuple(values)),
+        }
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, ValueError> {
+        let value = value.serialize(self)?;
+        let variant = variant.to_string();
+
+        if self.extensions.unwrap_variant_newtypes {
+            // the struct or the tuple shares the parentheses of the variant
+            match value {
+                Value::Struct(_, fields) => return Ok(Value::Struct(Some(variant), fields)),
+                Value::Tuple(values) | Value::NamedTuple(_, values) if values.len() != 1 => {
+                    return Ok(Value::NamedTuple(variant, values));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(Value::NamedTuple(variant, vec![value]))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeSeq, ValueError> {
+        Ok(SerializeSeq::new(self, SeqKind::List, len.unwrap_or(0)))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeSeq, ValueError> {
+        Ok(SerializeSeq::new(self, SeqKind::Tuple(None), len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        name: &'static str,
+        len: usize,
+    ) -> Result<SerializeSeq, ValueError> {
+        Ok(SerializeSeq::new(
+            self,
+            SeqKind::Tuple(self.name(name)),
+            len,
+        ))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeSeq, ValueError> {
+        Ok(SerializeSeq::new(
+            self,
+            SeqKind::Tuple(Some(variant.to_string())),
+            len,
+        ))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeMap, ValueError> {
+        Ok(SerializeMap {
+            serializer: self,
+            map: Map::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        name: &'static str,
+        _len: usize,
+    ) -> Result<SerializeStruct, ValueError> {
+        Ok(SerializeStruct {
+            serializer: self,
+            name: self.name(name),
+            fields: Map::new(),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeStruct, ValueError> {
+        Ok(SerializeStruct {
+            serializer: self,
+            name: Some(variant.to_string()),
+            fields: Map::new(),
+        })
+    }
+}
+
+enum SeqKind {
+    List,
+    /// With the name of the struct or of the variant
+    Tuple(Option<String>),
+}
+
+pub struct SerializeSeq {
+    serializer: Serializer,
+    kind: SeqKind,
+    values: Vec<Value>,
+}
+
+impl SerializeSeq {
+    fn new(serializer: Serializer, kind: SeqKind, len: usize) -> Self {
+        Self {
+            serializer,
+            kind,
+            values: Vec::with_capacity(len),
+        }
+    }
+
+    fn push<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.values.push(value.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        match self.kind {
+            SeqKind::List => Value::List(self.values),
+            SeqKind::Tuple(None) => Value::Tuple(self.values),
+            SeqKind::Tuple(Some(name)) => Value::NamedTuple(name, self.values),
+        }
+    }
+}
+
+impl ser::SerializeSeq for SerializeSeq {
+    type Ok = Value;
+    type Error = ValueError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ValueError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTuple for SerializeSeq {
+    type Ok = Value;
+    type Error = ValueError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ValueError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeSeq {
+    type Ok = Value;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ValueError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeSeq {
+    type Ok = Value;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        self.push(value)
+    }
+
+    fn end(self) -> Result<Value, ValueError> {
+        Ok(self.finish())
+    }
+}
+
+pub struct SerializeMap {
+    serializer: Serializer,
+    map: Map<Value>,
+    key: Option<Value>,
+}
+
+impl ser::SerializeMap for SerializeMap {
+    type Ok = Value;
+    type Error = ValueError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), ValueError> {
+        self.key = Some(key.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), ValueError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("serialize_value called before serialize_key"))?;
+        self.map.insert(key, value.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, ValueError> {
+        Ok(Value::Map(self.map))
+    }
+}
+
+pub struct SerializeStruct {
+    serializer: Serializer,
+    /// The name of the struct or of the variant
+    name: Option<String>,
+    fields: Map<String>,
+}
+
+impl SerializeStruct {
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), ValueError> {
+        self.fields.insert(key, value.serialize(self.serializer)?);
+        Ok(())
+    }
+
+    fn finish(self) -> Value {
+        Value::Struct(self.name, self.fields)
+    }
+}
+
+impl ser::SerializeStruct for SerializeStruct {
+    type Ok = Value;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ValueError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Value, ValueError> {
+        Ok(self.finish())
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeStruct {
+    type Ok = Value;
+    type Error = ValueError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), ValueError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<Value, ValueError> {
+        Ok(self.finish())
+    }
+}
+
+/// Struct names and enum variants can't be given to a serializer,
+/// so they are written with serde's externally tagged representation:
+/// a unit variant is its name, and a variant with fields is a map
+/// from its name to the fields.
+impl Serialize for Value {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use ser::{SerializeMap as _, SerializeSeq as _, SerializeTuple as _};
+
+        match self {
+            Value::Unit => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Char(c) => serializer.serialize_char(*c),
+            Value::Number(number) => match *number {
+                Number::I8(v) => serializer.serialize_i8(v),
+                Number::I16(v) => serializer.serialize_i16(v),
+                Number::I32(v) => serializer.serialize_i32(v),
+                Number::I64(v) => serializer.serialize_i64(v),
+                Number::I128(v) => serializer.serialize_i128(v),
+                Number::U8(v) => serializer.serialize_u8(v),
+                Number::U16(v) => serializer.serialize_u16(v),
+                Number::U32(v) => serializer.serialize_u32(v),
+                Number::U64(v) => serializer.serialize_u64(v),
+                Number::U128(v) => serializer.serialize_u128(v),
+                Number::F32(v) => serializer.serialize_f32(v.get()),
+                Number::F64(v) => serializer.serialize_f64(v.get()),
+            },
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Bytes(b) => serializer.serialize_bytes(b),
+            Value::Option(None) => serializer.serialize_none(),
+            Value::Option(Some(value)) => serializer.serialize_some(value),
+            Value::List(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Value::Tuple(values) => {
+                let mut tuple = serializer.serialize_tuple(values.len())?;
+                for value in values {
+                    tuple.serialize_element(value)?;
+                }
+                tuple.end()
+            }
+            Value::Map(map) => {
+                let mut ser_map = serializer.serialize_map(Some(map.len()))?;
+                for (key, value) in map.iter() {
+                    ser_map.serialize_entry(key, value)?;
+                }
+                ser_map.end()
+            }
+            Value::UnitStruct(name) => serializer.serialize_str(name),
+            Value::Struct(None, fields) => Fields(fields).serialize(serializer),
+            Value::Struct(Some(name), fields) => {
+                let mut ser_map = serializer.serialize_map(Some(1))?;
+                ser_map.serialize_entry(name, &Fields(fields))?;
+                ser_map.end()
+            }
+            Value::NamedTuple(name, values) => {
+                let mut ser_map = serializer.serialize_map(Some(1))?;
+                match values.as_slice() {
+                    [value] => ser_map.serialize_entry(name, value)?,
+                    values => ser_map.serialize_entry(name, &Tuple(values))?,
+                }
+                ser_map.end()
+            }
+        }
+    }
+}
+
+struct Fields<'a>(&'a Map<String>);
+
+impl Serialize for Fields<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use ser::SerializeMap as _;
+
+        let mut ser_map = serializer.serialize_map(Some(self.0.len()))?;
+        for (key, value) in self.0.iter() {
+            ser_map.serialize_entry(key, value)?;
+        }
+        ser_map.end()
+    }
+}
+
+struct Tuple<'a>(&'a [Value]);
+
+impl Serialize for Tuple<'_> {
+    fn serialize<S: ser::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use ser::SerializeTuple as _;
+
+        let mut tuple = serializer.serialize_tuple(self.0.len())?;
+        for value in self.0 {
+            tuple.serialize_element(value)?;
+        }
+        tuple.end()
+    }
+}
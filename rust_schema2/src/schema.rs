@@ -107,6 +107,9 @@ pub struct Struct {
     pub description: Option<String>,
     pub default: Option<Value>,
     pub fields: IndexMap<String, StructField>,
+    /// `struct A;`, which is serialized differently than `struct A {}`
+    #[serde(default)]
+    pub unit: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -171,4 +174,12 @@ impl RustSchema {
             None
         }
     }
+
+    pub fn as_enum(&self) -> Option<&Enum> {
+        if let RustSchemaKind::Enum(enum_) = &self.kind {
+            Some(enum_)
+        } else {
+            None
+        }
+    }
 }
@@ -117,7 +117,8 @@ fn expr_for_unit_struct(cont: &Container) -> TokenStream {
                     name: String::from(#name),
                     description: #description,
                     default: None,
-                    fields: indexmap::IndexMap::new()
+                    fields: indexmap::IndexMap::new(),
+                    unit: true,
                 }
             ),
         }
@@ -196,7 +197,8 @@ fn expr_for_struct(cont: &Container, fields: &[Field]) -> TokenStream {
                     name: String::from(#name),
                     description: #description,
                     default: #struct_default,
-                    fields: [#(#fields),*].into_iter().collect()
+                    fields: [#(#fields),*].into_iter().collect(),
+                    unit: false,
                 }
             ),
         }